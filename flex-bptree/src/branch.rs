@@ -2,26 +2,110 @@ use super::*;
 use bin_layout::{Array, Cursor};
 use std::mem;
 
+#[derive(Clone)]
 pub struct Branch<K, const SIZE: usize> {
     pub keys: Vec<K>,
     pub childs: Vec<u16>,
+    /// Encoded size of each key, So keys are encoded only once.
+    sizes: Vec<usize>,
+    /// Encoded size of the node, In bytes.
+    size: usize,
 }
 
 impl<K: Key, const SIZE: usize> Branch<K, SIZE> {
-    pub fn capacity() -> usize {
-        // BlockSize - (Node type (1) + keys len (2))
-        (SIZE - 3) / (K::SIZE + mem::size_of::<u16>())
-    }
+    /// Node type (1) + keys len (2) + first child (2)
+    const HEADER: usize = 5;
 
     pub fn new() -> Self {
         Self {
-            keys: Vec::with_capacity(Self::capacity() - 1),
-            childs: Vec::with_capacity(Self::capacity()),
+            keys: Vec::new(),
+            childs: Vec::new(),
+            sizes: Vec::new(),
+            size: Self::HEADER - mem::size_of::<u16>(),
+        }
+    }
+
+    /// A key may take a third of a branch (with its child) at most, So an overflowed branch can always be splitted in two.
+    pub fn max_key_size() -> usize {
+        (SIZE - Self::HEADER) / 3 - mem::size_of::<u16>()
+    }
+
+    pub fn size(&self) -> usize {
+        self.size
+    }
+
+    /// The node doesn't fit in a page, So it must be splitted.
+    pub fn is_overflow(&self) -> bool {
+        self.size > SIZE
+    }
+
+    /// `key` is the separator key with its encoded size.
+    pub fn create_root(key: (K, usize), left: u16, right: u16) -> Result<Self> {
+        let mut branch = Self::new();
+        branch.childs.push(left);
+        branch.size += mem::size_of::<u16>();
+        branch.insert(0, key, right)?;
+        Ok(branch)
+    }
+
+    /// Index of the child, That may contain the `key`.
+    pub fn lookup(&self, key: &K) -> usize {
+        match self
+            .keys
+            .binary_search_by(|k| k.partial_cmp(key).expect("Key can't be `NaN`"))
+        {
+            Ok(i) => i + 1,
+            Err(i) => i,
+        }
+    }
+
+    /// Insert the `child` after the child at `index`, With its separator `key` and the encoded size of the key.
+    ///
+    /// Returns [`Error::KeyTooLarge`] if the key is larger than [`Self::max_key_size`], The branch is unchanged.
+    pub fn insert(&mut self, index: usize, (key, size): (K, usize), child: u16) -> Result<()> {
+        if size > Self::max_key_size() {
+            return Err(Error::KeyTooLarge);
         }
+        self.size += size + mem::size_of::<u16>();
+        self.keys.insert(index, key);
+        self.sizes.insert(index, size);
+        self.childs.insert(index + 1, child);
+        Ok(())
     }
 
-    pub fn is_full(&self) -> bool {
-        self.childs.len() >= Self::capacity()
+    /// Remove the child at `index`, With its separator key. The first child takes the first key with it.
+    pub fn remove(&mut self, index: usize) {
+        self.childs.remove(index);
+        if !self.keys.is_empty() {
+            self.keys.remove(index.saturating_sub(1));
+            self.size -= self.sizes.remove(index.saturating_sub(1)) + mem::size_of::<u16>();
+        }
+    }
+
+    /// This function splits `Self` where both halves have nearly the same encoded size,
+    /// and returns the other half. with reminder key (and its encoded size).
+    pub fn split(&mut self) -> (Self, (K, usize)) {
+        let sizes: Vec<_> = self
+            .sizes
+            .iter()
+            .map(|k| k + mem::size_of::<u16>())
+            .collect();
+
+        let total = self.size - Self::HEADER;
+        // The reminder key is the first key, That crosses the half of the total size.
+        let (mut mid, mut left) = (0, 0);
+        while left + sizes[mid] <= total / 2 && mid + 1 < sizes.len() {
+            left += sizes[mid];
+            mid += 1;
+        }
+        let other = Self {
+            keys: self.keys.drain(mid + 1..).collect(),
+            childs: self.childs.drain(mid + 1..).collect(),
+            sizes: self.sizes.drain(mid + 1..).collect(),
+            size: Self::HEADER + total - left - sizes[mid],
+        };
+        self.size = Self::HEADER + left;
+        (other, (self.keys.pop().unwrap(), self.sizes.pop().unwrap()))
     }
 }
 
@@ -37,12 +121,16 @@ impl<'de, K: Key, const SIZE: usize> Decoder<'de, ()> for Branch<K, SIZE> {
     fn decoder(c: &mut Cursor<&'de [u8]>) -> Result<Self, ()> {
         let keys_len = u16::decoder(c)?;
         let mut this = Self::new();
+        let start = c.offset;
         for _ in 0..keys_len {
+            let key_start = c.offset;
             this.keys.push(K::decoder(c)?);
+            this.sizes.push(c.offset - key_start);
         }
         for _ in 0..keys_len + 1 {
             this.childs.push(u16::decoder(c)?);
         }
+        this.size += c.offset - start;
         Ok(this)
     }
 }
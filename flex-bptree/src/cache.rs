@@ -0,0 +1,101 @@
+use super::*;
+use std::{
+    collections::HashMap,
+    sync::atomic::{AtomicU64, Ordering::Relaxed},
+};
+
+/// Default memory budget of the node cache, In bytes.
+pub const DEFAULT_CACHE_SIZE: usize = 1024 * 1024;
+
+pub struct Link<K, V, const SIZE: usize> {
+    pub id: u16,
+    pub node: Node<K, V, SIZE>,
+    /// The node is changed, But isn't written yet.
+    pub dirty: bool,
+}
+
+struct Slot<K, V, const SIZE: usize> {
+    link: Link<K, V, SIZE>,
+    /// Tick of the last access, The least recently used node is evicted first.
+    used: AtomicU64,
+}
+
+/// Write-back cache of decoded nodes, Keyed by page number.
+pub struct Cache<K, V, const SIZE: usize> {
+    slots: HashMap<u16, Slot<K, V, SIZE>>,
+    tick: AtomicU64,
+    /// Encoded size of the cached nodes, In bytes.
+    pub size: usize,
+    /// Memory budget in bytes, Every node is counted by its encoded size.
+    pub budget: usize,
+}
+
+impl<K: Key, V: Value, const SIZE: usize> Cache<K, V, SIZE> {
+    pub fn new(budget: usize) -> Self {
+        Self {
+            slots: HashMap::new(),
+            tick: AtomicU64::new(0),
+            size: 0,
+            budget,
+        }
+    }
+
+    fn tick(&self) -> u64 {
+        self.tick.fetch_add(1, Relaxed) + 1
+    }
+
+    pub fn get(&self, id: u16) -> Option<&Node<K, V, SIZE>> {
+        self.slots.get(&id).map(|slot| {
+            slot.used.store(self.tick(), Relaxed);
+            &slot.link.node
+        })
+    }
+
+    /// Take the node out of the cache, The caller is responsible for putting it back.
+    pub fn take(&mut self, id: u16) -> Option<Link<K, V, SIZE>> {
+        let slot = self.slots.remove(&id)?;
+        self.size -= slot.link.node.size();
+        Some(slot.link)
+    }
+
+    pub fn put(&mut self, link: Link<K, V, SIZE>) {
+        self.size += link.node.size();
+        let used = AtomicU64::new(self.tick());
+        if let Some(old) = self.slots.insert(link.id, Slot { link, used }) {
+            self.size -= old.link.node.size();
+        }
+    }
+
+    pub fn is_over_budget(&self) -> bool {
+        self.size > self.budget
+    }
+
+    pub fn dirty(&mut self) -> impl Iterator<Item = &mut Link<K, V, SIZE>> {
+        self.slots
+            .values_mut()
+            .map(|slot| &mut slot.link)
+            .filter(|link| link.dirty)
+    }
+
+    /// Evicts clean nodes until the cache is within its budget, `keep` is never evicted.
+    ///
+    /// Leaves are evicted before branches, Each in least recently used order.
+    pub fn evict(&mut self, keep: u16) {
+        let mut clean: Vec<_> = self
+            .slots
+            .values()
+            .filter(|slot| !slot.link.dirty && slot.link.id != keep)
+            .map(|slot| {
+                let is_branch = matches!(slot.link.node, Node::Branch(_));
+                (is_branch, slot.used.load(Relaxed), slot.link.id)
+            })
+            .collect();
+        clean.sort_unstable();
+        for (_, _, id) in clean {
+            if !self.is_over_budget() {
+                break;
+            }
+            self.take(id);
+        }
+    }
+}
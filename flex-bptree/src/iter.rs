@@ -0,0 +1,109 @@
+use super::*;
+use std::ops::{Bound, RangeBounds};
+
+/// Entries of a [`BPlusTree`] within a range, In key order. See [`BPlusTree::range`].
+///
+/// Leaves are visited through their `next` links, And only a single leaf is decoded at a time.
+pub struct Range<'a, K: Key, V: Value, const SIZE: usize> {
+    tree: &'a BPlusTree<K, V, SIZE>,
+    /// Entries of the current leaf, That aren't yielded yet.
+    entries: std::vec::IntoIter<(K, V)>,
+    /// Page number of the next leaf, `0` if there is none.
+    next: u16,
+    end: Bound<K>,
+}
+
+impl<K: Key, V: Value, const SIZE: usize> BPlusTree<K, V, SIZE> {
+    /// #### _Blocking_
+    ///
+    /// Every entry, In key order.
+    pub fn iter(&self) -> Result<Range<'_, K, V, SIZE>> {
+        self.range(..)
+    }
+
+    /// #### _Blocking_
+    ///
+    /// Entries whose key is within the `range`, In key order.
+    /// Only the leaf that contains the start of the range is searched, Then the leaves are followed by their `next` links.
+    pub fn range(&self, range: impl RangeBounds<K>) -> Result<Range<'_, K, V, SIZE>> {
+        let start = match range.start_bound() {
+            Bound::Included(key) | Bound::Excluded(key) => Some(key),
+            Bound::Unbounded => None,
+        };
+        if let Bound::Included(key) | Bound::Excluded(key) = range.end_bound() {
+            ensure_ord(key)?;
+        }
+        start.map_or(Ok(()), ensure_ord)?;
+        let mut id = self.root;
+        loop {
+            let decoded;
+            let node = match self.cache.get(id) {
+                Some(node) => node,
+                None => {
+                    decoded = self.read(id)?;
+                    &decoded
+                }
+            };
+            match node {
+                Node::Branch(branch) => id = branch.childs[start.map_or(0, |key| branch.lookup(key))],
+                Node::Leaf(leaf) => {
+                    let skip = match range.start_bound() {
+                        Bound::Included(key) => leaf.binary_search(key).unwrap_or_else(|i| i),
+                        Bound::Excluded(key) => leaf.binary_search(key).map_or_else(|i| i, |i| i + 1),
+                        Bound::Unbounded => 0,
+                    };
+                    let mut entries = leaf.entries.clone();
+                    entries.drain(..skip);
+                    return Ok(Range {
+                        tree: self,
+                        entries: entries.into_iter(),
+                        next: leaf.next,
+                        end: range.end_bound().cloned(),
+                    });
+                }
+            }
+        }
+    }
+}
+
+impl<K: Key, V: Value, const SIZE: usize> Iterator for Range<'_, K, V, SIZE> {
+    type Item = Result<(K, V)>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            if let Some((key, value)) = self.entries.next() {
+                if !before_end(&key, self.end.as_ref()) {
+                    self.entries = Vec::new().into_iter();
+                    self.next = 0;
+                    return None;
+                }
+                return Some(Ok((key, value)));
+            }
+            if self.next == 0 {
+                return None;
+            }
+            let id = self.next;
+            self.next = 0;
+            let node = match self.tree.cache.get(id) {
+                Some(node) => Ok(node.clone()),
+                None => self.tree.read(id),
+            };
+            match node {
+                Ok(Node::Leaf(leaf)) => {
+                    self.next = leaf.next;
+                    self.entries = leaf.entries.into_iter();
+                }
+                Ok(Node::Branch(_)) => return Some(Err(Error::Corrupted { page: id.into() })),
+                Err(err) => return Some(Err(err)),
+            }
+        }
+    }
+}
+
+fn before_end<K: PartialOrd>(key: &K, end: Bound<&K>) -> bool {
+    match end {
+        Bound::Included(end) => key <= end,
+        Bound::Excluded(end) => key < end,
+        Bound::Unbounded => true,
+    }
+}
@@ -1,5 +1,7 @@
 use super::*;
-use bin_layout::Record;
+use bin_layout::{Array, Cursor};
+use branch::Branch;
+use entry::size_of;
 use std::mem;
 
 #[derive(Debug, Clone)]
@@ -8,33 +10,43 @@ pub enum SetOpt {
     UpdateOrInsert,
 }
 
-#[derive(Encoder, Decoder)]
+#[derive(Clone)]
 pub struct Leaf<K, V, const SIZE: usize> {
     pub next: u16,
     pub prev: u16,
-    pub entries: Record<u16, Vec<(K, V)>>,
+    pub entries: Vec<(K, V)>,
+    /// Encoded size of the key and the value of each entry, So entries are encoded only once.
+    sizes: Vec<(usize, usize)>,
+    /// Encoded size of the node, In bytes.
+    size: usize,
 }
 
-impl<K: Key, V: Key, const SIZE: usize> Leaf<K, V, SIZE> {
-    pub fn capacity() -> usize {
-        // BlockSize - (Node type (1) + next (2) + prev (2) + entries len (2))
-        (SIZE - 7) / (K::SIZE + V::SIZE)
+impl<K: Key, V: Value, const SIZE: usize> Leaf<K, V, SIZE> {
+    /// Node type (1) + next (2) + prev (2) + entries len (2)
+    const HEADER: usize = 7;
+
+    /// An entry may take half of a leaf at most, So an overflowed leaf can always be splitted in two.
+    pub fn max_entry_size() -> usize {
+        (SIZE - Self::HEADER) / 2
     }
 
     pub fn new() -> Self {
         Self {
             next: 0,
             prev: 0,
-            entries: Record::new(Vec::with_capacity(Self::capacity())),
+            entries: Vec::new(),
+            sizes: Vec::new(),
+            size: Self::HEADER,
         }
     }
 
-    pub fn is_full(&self) -> bool {
-        self.entries.len() >= Self::capacity()
+    pub fn size(&self) -> usize {
+        self.size
     }
 
-    pub fn is_half_full(&self) -> bool {
-        self.entries.len() > (Self::capacity() / 2)
+    /// The node doesn't fit in a page, So it must be splitted.
+    pub fn is_overflow(&self) -> bool {
+        self.size > SIZE
     }
 
     pub fn binary_search(&self, key: &K) -> Result<usize, usize> {
@@ -42,28 +54,110 @@ impl<K: Key, V: Key, const SIZE: usize> Leaf<K, V, SIZE> {
             .binary_search_by(|(k, _)| k.partial_cmp(key).expect("Key can't be `NaN`"))
     }
 
-    pub fn insert(&mut self, key: K, val: V, opt: SetOpt) -> Option<V> {
+    /// Returns [`Error::KeyTooLarge`] if the entry is larger than [`Self::max_entry_size`],
+    /// Or the key is larger than [`Branch::max_key_size`] (it may become a separator key). The leaf is unchanged.
+    pub fn insert(&mut self, key: K, val: V, opt: SetOpt) -> Result<Option<V>> {
         match self.binary_search(&key) {
-            Ok(index) => {
-                let entry = self.entries.get_mut(index);
-                match opt {
-                    SetOpt::FindOrInsert => entry.map(|kv| kv.1),
-                    SetOpt::UpdateOrInsert => entry.map(|kv| mem::replace(&mut kv.1, val)),
+            Ok(index) => match opt {
+                SetOpt::FindOrInsert => Ok(Some(self.entries[index].1.clone())),
+                SetOpt::UpdateOrInsert => {
+                    let (key_size, old) = self.sizes[index];
+                    let new = size_of(&val);
+                    if key_size + new > Self::max_entry_size() {
+                        return Err(Error::KeyTooLarge);
+                    }
+                    self.size = self.size - old + new;
+                    self.sizes[index].1 = new;
+                    Ok(Some(mem::replace(&mut self.entries[index].1, val)))
                 }
-            }
+            },
             Err(index) => {
+                let sizes = (size_of(&key), size_of(&val));
+                if sizes.0 + sizes.1 > Self::max_entry_size()
+                    || sizes.0 > Branch::<K, SIZE>::max_key_size()
+                {
+                    return Err(Error::KeyTooLarge);
+                }
+                self.size += sizes.0 + sizes.1;
                 self.entries.insert(index, (key, val));
-                None
+                self.sizes.insert(index, sizes);
+                Ok(None)
             }
         }
     }
 
-    pub fn split_at_mid(&mut self) -> Self {
-        let mid_point = self.entries.len() / 2;
-        Self {
+    pub fn get(&self, key: &K) -> Option<V> {
+        let index = self.binary_search(key).ok()?;
+        Some(self.entries[index].1.clone())
+    }
+
+    pub fn remove(&mut self, key: &K) -> Option<(K, V)> {
+        let index = self.binary_search(key).ok()?;
+        let (key, val) = self.entries.remove(index);
+        let (key_size, val_size) = self.sizes.remove(index);
+        self.size -= key_size + val_size;
+        Some((key, val))
+    }
+
+    /// First key of the leaf with its encoded size, That is used as a separator key.
+    pub fn first_key(&self) -> (K, usize) {
+        (self.entries[0].0.clone(), self.sizes[0].0)
+    }
+
+    /// This function splits `Self` where both halves have nearly the same encoded size, and returns the right half.
+    pub fn split(&mut self) -> Self {
+        let sizes: Vec<_> = self.sizes.iter().map(|(k, v)| k + v).collect();
+
+        let total = self.size - Self::HEADER;
+        // The first entry, That crosses the half of the total size.
+        let (mut index, mut left) = (0, 0);
+        while left + sizes[index] <= total / 2 {
+            left += sizes[index];
+            index += 1;
+        }
+        // Either the crossing entry goes to the right or to the left half, whichever is smaller.
+        if left + sizes[index] < total - left {
+            left += sizes[index];
+            index += 1;
+        }
+        let right = Self {
             next: 0,
             prev: 0,
-            entries: Record::new(self.entries.drain(mid_point..).collect()),
+            entries: self.entries.drain(index..).collect(),
+            sizes: self.sizes.drain(index..).collect(),
+            size: Self::HEADER + total - left,
+        };
+        self.size = Self::HEADER + left;
+        right
+    }
+}
+
+impl<K: Key, V: Value, const SIZE: usize> Encoder for Leaf<K, V, SIZE> {
+    fn encoder(self, buf: &mut impl Array<u8>) {
+        self.next.encoder(buf);
+        self.prev.encoder(buf);
+        (self.entries.len() as u16).encoder(buf);
+        self.entries
+            .into_iter()
+            .for_each(|entry| entry.encoder(buf));
+    }
+}
+
+impl<'de, K: Key, V: Value, const SIZE: usize> Decoder<'de, ()> for Leaf<K, V, SIZE> {
+    fn decoder(c: &mut Cursor<&'de [u8]>) -> Result<Self, ()> {
+        let mut this = Self::new();
+        this.next = u16::decoder(c)?;
+        this.prev = u16::decoder(c)?;
+        let len = u16::decoder(c)?;
+        for _ in 0..len {
+            let start = c.offset;
+            let key = K::decoder(c)?;
+            let mid = c.offset;
+            let val = V::decoder(c)?;
+            this.entries.push((key, val));
+            this.sizes.push((mid - start, c.offset - mid));
+            this.size += c.offset - start;
         }
+        Ok(this)
     }
 }
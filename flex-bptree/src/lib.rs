@@ -1,64 +1,476 @@
 mod branch;
+mod cache;
 mod entry;
+mod iter;
 mod leaf;
 mod node;
+mod set;
 
-use bin_layout::{Cursor, Decoder, Encoder};
+use bin_layout::{stack_array::ArrayBuf, Cursor, Decoder, Encoder};
+use branch::Branch;
+use cache::{Cache, Link};
 use flex_page::Pages;
 use leaf::Leaf;
 
 use std::{fs::File, io, path::Path};
 
-use entry::Key;
 use node::Node;
 
-struct Link<K, V, const SIZE: usize> {
-    id: u16,
-    node: Node<K, V, SIZE>,
-}
+pub use cache::DEFAULT_CACHE_SIZE;
+pub use entry::{Key, Value};
+pub use iter::Range;
+pub use flex::{Error, Result};
+pub use flex_derive::{BPlusTreeKey as Key, BPlusTreeValue as Value};
+pub use leaf::SetOpt;
+pub use set::BPlusTreeSet;
+
+/// Separator key (with its encoded size) and page number of the new right node, When a node is splitted.
+type Split<K> = Option<((K, usize), u16)>;
+
+/// Every file starts with the magic and the format [`VERSION`], See [`Metadata`].
+pub const MAGIC: [u8; 4] = *b"FBPV";
+pub const VERSION: u8 = 1;
 
 #[derive(Decoder, Encoder)]
 pub struct Metadata {
+    pub magic: [u8; 4],
+    pub version: u8,
+    pub page_size: u32,
+    /// `1` while the tree is opened, It is cleared on drop (or [`BPlusTree::close`]).
+    pub is_opened: u8,
     pub root_id: u16,
+    /// Head of the free page list, `0` if it is empty.
+    pub free: u16,
+}
+
+impl Metadata {
+    fn new<const SIZE: usize>(root_id: u16, free: u16, is_opened: u8) -> Self {
+        Self {
+            magic: MAGIC,
+            version: VERSION,
+            page_size: SIZE as u32,
+            is_opened,
+            root_id,
+            free,
+        }
+    }
+
+    /// Returns [`Error::FormatMismatch`] if the file isn't written with the current [`VERSION`] and page size,
+    /// And [`Error::AlreadyOpen`] if it is opened (or wasn't closed properly).
+    fn decode<const SIZE: usize>(page: &[u8]) -> Result<Self> {
+        let metadata: io::Result<Self> = Decoder::decoder(&mut Cursor::new(page));
+        let metadata = metadata.map_err(|_| Error::Corrupted { page: 0 })?;
+        let expected = || format!("format version {}", VERSION);
+        if metadata.magic != MAGIC {
+            return Err(Error::FormatMismatch {
+                expected: expected(),
+                found: "a file without format version".into(),
+            });
+        }
+        if metadata.version != VERSION {
+            return Err(Error::FormatMismatch {
+                expected: expected(),
+                found: format!("format version {}", metadata.version),
+            });
+        }
+        if metadata.page_size != SIZE as u32 {
+            return Err(Error::FormatMismatch {
+                expected: format!("page size {}", SIZE),
+                found: format!("page size {}", metadata.page_size),
+            });
+        }
+        if metadata.is_opened == 1 {
+            return Err(Error::AlreadyOpen);
+        }
+        Ok(metadata)
+    }
 }
 
-pub struct BPlusTree<K, V, const SIZE: usize> {
+/// Changed nodes are kept in a write-back cache, They are written with the [`Metadata`]
+/// on [`BPlusTree::flush`], On drop, Or when the cache exceeds its memory budget.
+pub struct BPlusTree<K: Key, V: Value, const SIZE: usize> {
     pages: Pages<SIZE>,
-    root: Link<K, V, SIZE>,
+    root: u16,
+    /// See [`Metadata::free`].
+    free: u16,
+    cache: Cache<K, V, SIZE>,
+    /// Cleared by [`BPlusTree::close`], So drop doesn't write the tree again.
+    is_opened: bool,
 }
 
-impl<K: Key, V: Key, const SIZE: usize> BPlusTree<K, V, SIZE> {
+impl<K: Key, V: Value, const SIZE: usize> BPlusTree<K, V, SIZE> {
     /// #### _Blocking_
-    pub fn open(path: impl AsRef<Path>) -> io::Result<Self> {
+    ///
+    /// A file can be opened by a single tree at a time, Otherwise [`Error::AlreadyOpen`] is returned.
+    pub fn open(path: impl AsRef<Path>) -> Result<Self> {
         let file = File::options()
             .read(true)
             .write(true)
             .create(true)
+            .truncate(false)
             .open(path)?;
 
         let pages = Pages::open(file)?;
 
-        let root = if pages.len() == 0 {
+        let (root, free) = if pages.len() == 0 {
             pages.alloc(2)?; // 1 for metadata, 1 for root node
-            Link {
+            let root = Link {
                 id: 1,
                 node: Node::Leaf(Leaf::new()),
-            }
+                dirty: true,
+            };
+            (root, 0)
         } else {
-            let data = pages.read(0)?;
-            let mut cursor = Cursor::new(data.as_ref());
-            let metadata: Result<_, io::Error> = Metadata::decoder(&mut cursor);
+            let metadata = Metadata::decode::<SIZE>(pages.read(0)?.as_ref())?;
 
-            let id = metadata?.root_id;
+            let id = metadata.root_id;
             let buf = pages.read(id.into())?;
-            Link {
+            let root = Link {
                 id,
-                node: Node::decoder(buf.as_ref()),
+                node: Node::decoder(id, buf.as_ref())?,
+                dirty: false,
+            };
+            (root, metadata.free)
+        };
+
+        let mut cache = Cache::new(DEFAULT_CACHE_SIZE);
+        let root_id = root.id;
+        cache.put(root);
+        // `metadata.is_opened` flag.
+        pages.write(0, encode(Metadata::new::<SIZE>(root_id, free, 1)))?;
+        Ok(Self {
+            pages,
+            root: root_id,
+            free,
+            cache,
+            is_opened: true,
+        })
+    }
+
+    /// Memory budget of the node cache, In bytes.
+    pub fn cache_size(&self) -> usize {
+        self.cache.budget
+    }
+
+    /// Memory used by the node cache, In bytes. Nodes are counted by their encoded size.
+    pub fn cache_used(&self) -> usize {
+        self.cache.size
+    }
+
+    /// #### _Blocking_
+    ///
+    /// If the cache exceeds the new budget, Dirty nodes are written and the least recently used nodes are evicted.
+    pub fn set_cache_size(&mut self, bytes: usize) -> Result<()> {
+        self.cache.budget = bytes;
+        self.settle()
+    }
+
+    /// #### _Blocking_
+    ///
+    /// Same as drop, But the error is returned. Drop ignores it.
+    pub fn close(mut self) -> Result<()> {
+        self._close()
+    }
+
+    /// Writes every dirty node, And clears the `is_opened` flag of the [`Metadata`].
+    fn _close(&mut self) -> Result<()> {
+        if self.is_opened {
+            self.flush()?;
+            self.write_metadata(0)?;
+            self.is_opened = false;
+        }
+        Ok(())
+    }
+
+    /// #### _Blocking_
+    ///
+    /// Writes every dirty node and the [`Metadata`], Cached nodes are kept in memory.
+    pub fn flush(&mut self) -> Result<()> {
+        for link in self.cache.dirty() {
+            let page = to_page(link.node.clone().encode());
+            self.pages.write(link.id.into(), page)?;
+            link.dirty = false;
+        }
+        self.write_metadata(1)
+    }
+
+    fn write_metadata(&self, is_opened: u8) -> Result<()> {
+        let metadata = Metadata::new::<SIZE>(self.root, self.free, is_opened);
+        Ok(self.pages.write(0, encode(metadata))?)
+    }
+
+    /// #### _Blocking_
+    pub fn get(&self, key: &K) -> Result<Option<V>> {
+        ensure_ord(key)?;
+        let mut id = self.root;
+        loop {
+            let decoded;
+            let node = match self.cache.get(id) {
+                Some(node) => node,
+                None => {
+                    decoded = self.read(id)?;
+                    &decoded
+                }
+            };
+            id = match node {
+                Node::Leaf(leaf) => return Ok(leaf.get(key)),
+                Node::Branch(branch) => branch.childs[branch.lookup(key)],
+            }
+        }
+    }
+
+    /// #### _Blocking_
+    ///
+    /// Returns the old value, If the key was already present.
+    ///
+    /// An encoded entry may take half of a page (excluding the node header) at most,
+    /// Otherwise [`Error::KeyTooLarge`] is returned and the tree is unchanged.
+    pub fn set(&mut self, key: K, value: V, opt: SetOpt) -> Result<Option<V>> {
+        ensure_ord(&key)?;
+        let mut root = self.take(self.root)?;
+        let result = self._set(&mut root, key, value, opt);
+        let id = root.id;
+        self.cache.put(root);
+        let (ret, split) = result?;
+        if let Some((mid, right)) = split {
+            // The root is splitted, So the height of the tree grows.
+            self.root = self.create(Node::Branch(Branch::create_root(mid, id, right)?))?;
+        }
+        self.settle()?;
+        Ok(ret)
+    }
+
+    /// Insert the entry into the node of `link`, The caller is responsible for putting the `link` back.
+    ///
+    /// Returns the old value, And the split of the node.
+    fn _set(
+        &mut self,
+        link: &mut Link<K, V, SIZE>,
+        key: K,
+        value: V,
+        opt: SetOpt,
+    ) -> Result<(Option<V>, Split<K>)> {
+        match &mut link.node {
+            Node::Leaf(leaf) => {
+                let found = matches!(opt, SetOpt::FindOrInsert);
+                let ret = leaf.insert(key, value, opt)?;
+                // If `FindOrInsert` option is enable, And if the key is founded, Nothing is changed.
+                if found && ret.is_some() {
+                    return Ok((ret, None));
+                }
+                link.dirty = true;
+                if !leaf.is_overflow() {
+                    return Ok((ret, None));
+                }
+                let mut right = leaf.split();
+                right.prev = link.id;
+                right.next = leaf.next;
+                let mid = right.first_key();
+                let next = right.next;
+                let right_id = self.create(Node::Leaf(right))?;
+                if next != 0 {
+                    let mut next = self.take(next)?;
+                    if let Node::Leaf(next_leaf) = &mut next.node {
+                        next_leaf.prev = right_id;
+                        next.dirty = true;
+                    }
+                    self.cache.put(next);
+                }
+                leaf.next = right_id;
+                Ok((ret, Some((mid, right_id))))
+            }
+            Node::Branch(branch) => {
+                let index = branch.lookup(&key);
+                let mut child = self.take(branch.childs[index])?;
+                let result = self._set(&mut child, key, value, opt);
+                self.cache.put(child);
+                let (ret, split) = result?;
+                if let Some((mid, right)) = split {
+                    branch.insert(index, mid, right)?;
+                    link.dirty = true;
+                    if branch.is_overflow() {
+                        let (other, mid) = branch.split();
+                        return Ok((ret, Some((mid, self.create(Node::Branch(other))?))));
+                    }
+                }
+                Ok((ret, None))
+            }
+        }
+    }
+
+    /// #### _Blocking_
+    ///
+    /// A leaf that is left empty is removed from the tree, And its page is reused.
+    /// Other nodes are not merged, So they may be left less than half full.
+    pub fn delete(&mut self, key: &K) -> Result<Option<(K, V)>> {
+        ensure_ord(key)?;
+        let mut root = self.take(self.root)?;
+        let result = self._delete(&mut root, key);
+        self.cache.put(root);
+        let (ret, _) = result?;
+        self.shrink()?;
+        self.settle()?;
+        Ok(ret)
+    }
+
+    /// If the root branch is left with a single child, That child becomes the new root.
+    /// So the height of the tree shrinks.
+    fn shrink(&mut self) -> Result<()> {
+        loop {
+            let root = self.take(self.root)?;
+            match &root.node {
+                Node::Branch(branch) if branch.childs.len() == 1 => {
+                    self.root = branch.childs[0];
+                    self.free(root.id)?;
+                }
+                _ => {
+                    self.cache.put(root);
+                    return Ok(());
+                }
+            }
+        }
+    }
+
+    /// Remove the entry from the node of `link`, The caller is responsible for putting the `link` back.
+    ///
+    /// Returns the removed entry, And whether the node is left empty. An empty child is removed from its parent.
+    fn _delete(&mut self, link: &mut Link<K, V, SIZE>, key: &K) -> Result<(Option<(K, V)>, bool)> {
+        match &mut link.node {
+            Node::Leaf(leaf) => {
+                let ret = leaf.remove(key);
+                link.dirty |= ret.is_some();
+                Ok((ret, leaf.entries.is_empty()))
+            }
+            Node::Branch(branch) => {
+                let index = branch.lookup(key);
+                let mut child = self.take(branch.childs[index])?;
+                let (ret, empty) = match self._delete(&mut child, key) {
+                    Ok(ok) => ok,
+                    Err(err) => {
+                        self.cache.put(child);
+                        return Err(err);
+                    }
+                };
+                if !(ret.is_some() && empty) {
+                    self.cache.put(child);
+                    return Ok((ret, false));
+                }
+                if let Node::Leaf(leaf) = &child.node {
+                    self.unlink(leaf.prev, leaf.next)?;
+                }
+                self.free(child.id)?;
+                branch.remove(index);
+                link.dirty = true;
+                Ok((ret, branch.childs.is_empty()))
+            }
+        }
+    }
+
+    /// Link the leaves on both sides of a removed leaf.
+    fn unlink(&mut self, prev: u16, next: u16) -> Result<()> {
+        for (id, link_to) in [(prev, next), (next, prev)] {
+            if id == 0 {
+                continue;
+            }
+            let mut link = self.take(id)?;
+            if let Node::Leaf(leaf) = &mut link.node {
+                if id == prev {
+                    leaf.next = link_to;
+                } else {
+                    leaf.prev = link_to;
+                }
+                link.dirty = true;
+            }
+            self.cache.put(link);
+        }
+        Ok(())
+    }
+
+    /// If the cache exceeds its budget, Dirty nodes are written and clean nodes are evicted until it is within the budget.
+    /// Leaves are evicted first, So branches are kept in memory. The root is never evicted.
+    fn settle(&mut self) -> Result<()> {
+        if self.cache.is_over_budget() {
+            self.flush()?;
+            self.cache.evict(self.root);
+        }
+        Ok(())
+    }
+
+    /// Take the node out of the cache, Or read it from the disk.
+    fn take(&mut self, id: u16) -> Result<Link<K, V, SIZE>> {
+        match self.cache.take(id) {
+            Some(link) => Ok(link),
+            None => Ok(Link {
+                id,
+                node: self.read(id)?,
+                dirty: false,
+            }),
+        }
+    }
+
+    fn read(&self, id: u16) -> Result<Node<K, V, SIZE>> {
+        Node::decoder(id, self.pages.read(id.into())?.as_ref())
+    }
+
+    /// Reuses a page from the free list (or allocates one) for the `node`, It is written on flush.
+    ///
+    /// Returns [`Error::InvalidInput`] if the page number doesn't fit in `u16`.
+    fn create(&mut self, node: Node<K, V, SIZE>) -> Result<u16> {
+        let id = match self.free {
+            0 => {
+                let id = self.pages.alloc(1)?;
+                u16::try_from(id).map_err(|_| {
+                    Error::InvalidInput(format!("The page number {} doesn't fit in `u16`.", id))
+                })?
+            }
+            id => {
+                let page = self.pages.read(id.into())?;
+                let free: io::Result<(u8, u16)> = Decoder::decoder(&mut Cursor::new(page.as_ref()));
+                self.free = free.map_err(|_| Error::Corrupted { page: id.into() })?.1;
+                id
             }
         };
+        self.cache.put(Link {
+            id,
+            node,
+            dirty: true,
+        });
+        Ok(id)
+    }
 
-        Ok(Self { pages, root })
+    /// Pushes the page to the free list, The node must be taken out of the cache.
+    ///
+    /// Free page layout: Node type (2) + next free page (2)
+    fn free(&mut self, id: u16) -> Result<()> {
+        self.pages.write(id.into(), encode((2u8, self.free)))?;
+        self.free = id;
+        Ok(())
     }
+}
+
+impl<K: Key, V: Value, const SIZE: usize> Drop for BPlusTree<K, V, SIZE> {
+    fn drop(&mut self) {
+        // Use `close` to handle the error.
+        let _ = self._close();
+    }
+}
+
+/// Keys must be comparable with themselves, So `NaN` is rejected before it reaches a node.
+fn ensure_ord<K: Key>(key: &K) -> Result<()> {
+    match key.partial_cmp(key) {
+        Some(_) => Ok(()),
+        None => Err(Error::InvalidKey),
+    }
+}
+
+fn encode<const SIZE: usize>(value: impl Encoder) -> [u8; SIZE] {
+    let mut arr = ArrayBuf::new();
+    value.encoder(&mut arr);
+    to_page(arr)
+}
 
-    fn set(&mut self, key: K, value: V) {}
+fn to_page<const SIZE: usize>(arr: ArrayBuf<u8, SIZE>) -> [u8; SIZE] {
+    let mut buf = [0; SIZE];
+    buf[..arr.len()].copy_from_slice(&arr);
+    buf
 }
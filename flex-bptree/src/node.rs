@@ -3,12 +3,13 @@ use bin_layout::{stack_array::ArrayBuf, Array, Cursor};
 use super::*;
 use crate::{branch::Branch, leaf::Leaf};
 
+#[derive(Clone)]
 pub enum Node<K, V, const SIZE: usize> {
     Leaf(Leaf<K, V, SIZE>),
     Branch(Branch<K, SIZE>),
 }
 
-impl<K: Key, V: Key, const SIZE: usize> Node<K, V, SIZE> {
+impl<K: Key, V: Value, const SIZE: usize> Node<K, V, SIZE> {
     pub fn encode(self) -> ArrayBuf<u8, SIZE> {
         let mut arr = ArrayBuf::new();
         match self {
@@ -24,12 +25,22 @@ impl<K: Key, V: Key, const SIZE: usize> Node<K, V, SIZE> {
         arr
     }
 
-    pub fn decoder(buf: &[u8]) -> Self {
+    /// Encoded size of the node, In bytes.
+    pub fn size(&self) -> usize {
+        match self {
+            Node::Leaf(leaf) => leaf.size(),
+            Node::Branch(branch) => branch.size(),
+        }
+    }
+
+    /// `id` is the page number of the node, It is reported if the node is corrupted.
+    pub fn decoder(id: u16, buf: &[u8]) -> Result<Self> {
+        let corrupted = |()| Error::Corrupted { page: id.into() };
         let mut c = Cursor::new(buf);
-        match (u8::decoder(&mut c) as Result<u8, ()>).unwrap() {
-            0 => Node::Leaf(Leaf::decoder(&mut c).unwrap()),
-            1 => Node::Branch(Branch::decoder(&mut c).unwrap()),
-            _ => panic!("invalid node type"),
+        match u8::decoder(&mut c).map_err(corrupted)? {
+            0 => Ok(Node::Leaf(Leaf::decoder(&mut c).map_err(corrupted)?)),
+            1 => Ok(Node::Branch(Branch::decoder(&mut c).map_err(corrupted)?)),
+            _ => Err(corrupted(())),
         }
     }
 }
\ No newline at end of file
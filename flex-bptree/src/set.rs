@@ -0,0 +1,61 @@
+use super::*;
+use std::ops::RangeBounds;
+
+/// An ordered set of keys, Backed by [`BPlusTree`] whose values are `()`.
+///
+/// The value takes zero bytes, So a leaf holds only keys.
+pub struct BPlusTreeSet<K: Key, const SIZE: usize> {
+    tree: BPlusTree<K, (), SIZE>,
+}
+
+impl<K: Key, const SIZE: usize> BPlusTreeSet<K, SIZE> {
+    /// #### _Blocking_
+    pub fn open(path: impl AsRef<Path>) -> Result<Self> {
+        BPlusTree::open(path).map(|tree| Self { tree })
+    }
+
+    /// #### _Blocking_
+    pub fn contains(&self, key: &K) -> Result<bool> {
+        Ok(self.tree.get(key)?.is_some())
+    }
+
+    /// #### _Blocking_
+    ///
+    /// Returns `true`, If the key was not already present.
+    pub fn insert(&mut self, key: K) -> Result<bool> {
+        Ok(self.tree.set(key, (), SetOpt::FindOrInsert)?.is_none())
+    }
+
+    /// #### _Blocking_
+    ///
+    /// Returns `true`, If the key was present.
+    pub fn remove(&mut self, key: &K) -> Result<bool> {
+        Ok(self.tree.delete(key)?.is_some())
+    }
+
+    /// #### _Blocking_
+    ///
+    /// Every key, In order.
+    pub fn iter(&self) -> Result<impl Iterator<Item = Result<K>> + '_> {
+        self.range(..)
+    }
+
+    /// #### _Blocking_
+    ///
+    /// Keys within the `range`, In order. See [`BPlusTree::range`].
+    pub fn range(&self, range: impl RangeBounds<K>) -> Result<impl Iterator<Item = Result<K>> + '_> {
+        Ok(self.tree.range(range)?.map(|entry| entry.map(|(key, _)| key)))
+    }
+
+    /// #### _Blocking_
+    pub fn flush(&mut self) -> Result<()> {
+        self.tree.flush()
+    }
+
+    /// #### _Blocking_
+    ///
+    /// See [`BPlusTree::close`].
+    pub fn close(self) -> Result<()> {
+        self.tree.close()
+    }
+}
@@ -0,0 +1,44 @@
+use flex_bptree::{BPlusTree, SetOpt, DEFAULT_CACHE_SIZE};
+use std::{fs::remove_file, io::Result};
+
+type Tree = BPlusTree<u32, u64, 128>;
+
+#[test]
+fn write_back_cache() -> Result<()> {
+    let _ = remove_file("write_back_cache");
+    {
+        let mut tree = Tree::open("write_back_cache")?;
+        assert_eq!(tree.cache_size(), DEFAULT_CACHE_SIZE);
+        for i in 0..1000 {
+            tree.set(i, i as u64, SetOpt::UpdateOrInsert)?;
+        }
+        tree.flush()?;
+    }
+    {
+        // A file is opened by a single tree at a time, So the writer is dropped before it is reopened.
+        let reader = Tree::open("write_back_cache")?;
+        for i in 0..1000 {
+            assert_eq!(reader.get(&i)?, Some(i as u64));
+        }
+    }
+    {
+        let mut tree = Tree::open("write_back_cache")?;
+        // A small budget evicts the cache, Dirty nodes are written first.
+        tree.set_cache_size(128 * 4)?;
+        for i in 1000..3000 {
+            tree.set(i, i as u64, SetOpt::UpdateOrInsert)?;
+        }
+        // Only enough nodes are evicted, To get back under the budget.
+        assert!(tree.cache_used() <= 128 * 4, "{}", tree.cache_used());
+        assert!(tree.cache_used() > 128 * 2, "{}", tree.cache_used());
+        for i in (0..3000).step_by(3) {
+            assert_eq!(tree.delete(&i)?, Some((i, i as u64)));
+        }
+    }
+    let tree = Tree::open("write_back_cache")?;
+    for i in 0..3000 {
+        assert_eq!(tree.get(&i)?, (i % 3 != 0).then_some(i as u64));
+    }
+    drop(tree);
+    remove_file("write_back_cache")
+}
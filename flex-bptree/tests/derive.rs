@@ -0,0 +1,43 @@
+use bin_layout::{Decoder, Encoder};
+use flex_bptree::{BPlusTree, Key, SetOpt, Value};
+use std::{fs::remove_file, io::Result};
+
+#[derive(Key, Encoder, Decoder, Debug, Clone)]
+struct Name {
+    last: [u8; 4],
+    first: [u8; 4],
+}
+
+#[derive(Value, Encoder, Decoder, Debug, Clone, PartialEq)]
+struct Person {
+    age: u8,
+    score: f32,
+}
+
+#[test]
+fn derive_key_value() -> Result<()> {
+    let _ = remove_file("derive_key_value");
+    let name = |last: &[u8; 4], first: &[u8; 4]| Name {
+        last: *last,
+        first: *first,
+    };
+    assert!(name(b"abcd", b"zzzz") < name(b"abce", b"aaaa"));
+    assert!(name(b"abcd", b"aaaa") < name(b"abcd", b"aaab"));
+    {
+        let mut tree = BPlusTree::<Name, Person, 128>::open("derive_key_value")?;
+        for i in 0..200u8 {
+            let person = Person {
+                age: i,
+                score: i as f32,
+            };
+            assert_eq!(
+                tree.set(name(b"smit", &[i; 4]), person, SetOpt::UpdateOrInsert)?,
+                None
+            );
+        }
+        let person = tree.get(&name(b"smit", &[7; 4]))?;
+        assert_eq!(person, Some(Person { age: 7, score: 7.0 }));
+        assert_eq!(tree.get(&name(b"jone", &[7; 4]))?, None);
+    }
+    remove_file("derive_key_value")
+}
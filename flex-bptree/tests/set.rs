@@ -0,0 +1,52 @@
+use flex_bptree::BPlusTreeSet;
+use std::{fs::remove_file, io::Result, ops::Bound};
+
+#[test]
+fn ordered_set() -> Result<()> {
+    let _ = remove_file("ordered_set");
+    {
+        let mut set = BPlusTreeSet::<u32, 128>::open("ordered_set")?;
+        for key in (0..3000).rev() {
+            assert!(set.insert(key)?);
+        }
+        assert!(!set.insert(7)?);
+        assert!(set.remove(&7)?);
+        assert!(!set.remove(&7)?);
+    }
+    let set = BPlusTreeSet::<u32, 128>::open("ordered_set")?;
+    for key in 0..3000 {
+        assert_eq!(set.contains(&key)?, key != 7);
+    }
+    assert!(!set.contains(&3000)?);
+    drop(set);
+    remove_file("ordered_set")
+}
+
+#[test]
+fn ordered_iter() -> Result<()> {
+    let _ = remove_file("ordered_iter");
+    {
+        let mut set = BPlusTreeSet::<u32, 128>::open("ordered_iter")?;
+        assert_eq!(set.iter()?.count(), 0);
+        for i in 0..3000 {
+            set.insert(i * 7919 % 3000)?;
+        }
+        for key in (0..3000).filter(|k| k % 3 == 0) {
+            set.remove(&key)?;
+        }
+        let expected: Vec<u32> = (0..3000).filter(|k| k % 3 != 0).collect();
+        // Leaves are followed by their links, So the keys are sorted.
+        assert_eq!(set.iter()?.collect::<flex_bptree::Result<Vec<_>>>()?, expected);
+
+        let keys = |range| -> flex_bptree::Result<Vec<u32>> { set.range(range)?.collect() };
+        assert_eq!(keys((Bound::Included(1000), Bound::Excluded(1010)))?, [1000, 1001, 1003, 1004, 1006, 1007, 1009]);
+        assert_eq!(keys((Bound::Excluded(1001), Bound::Included(1004)))?, [1003, 1004]);
+        assert_eq!(keys((Bound::Included(2995), Bound::Unbounded))?, [2995, 2996, 2998, 2999]);
+        assert_eq!(keys((Bound::Unbounded, Bound::Excluded(5)))?, [1, 2, 4]);
+        assert_eq!(keys((Bound::Included(3000), Bound::Unbounded))?, []);
+    }
+    let set = BPlusTreeSet::<u32, 128>::open("ordered_iter")?;
+    assert_eq!(set.range(1500..)?.count(), 1000);
+    drop(set);
+    remove_file("ordered_iter")
+}
@@ -0,0 +1,130 @@
+use flex_bptree::{BPlusTree, SetOpt};
+use std::{fs::remove_file, io::Result};
+
+type Tree = BPlusTree<u64, u32, 256>;
+
+#[test]
+fn set_get_delete() -> Result<()> {
+    let _ = remove_file("set_get_delete");
+    {
+        let mut tree = Tree::open("set_get_delete")?;
+        for i in 0..2000u64 {
+            let key = i * 7919 % 2000;
+            assert_eq!(tree.set(key, key as u32, SetOpt::UpdateOrInsert)?, None);
+        }
+        assert_eq!(tree.set(4, 40, SetOpt::UpdateOrInsert)?, Some(4));
+        assert_eq!(tree.set(4, 400, SetOpt::FindOrInsert)?, Some(40));
+        assert_eq!(tree.get(&4)?, Some(40));
+        assert_eq!(tree.get(&2000)?, None);
+
+        for key in (0..2000u64).filter(|k| k % 2 == 1) {
+            assert_eq!(tree.delete(&key)?.map(|(_, v)| v), Some(key as u32));
+        }
+        assert_eq!(tree.delete(&1)?, None);
+    }
+    {
+        // The root and metadata are written on drop.
+        let tree = Tree::open("set_get_delete")?;
+        for key in 6..2000u64 {
+            let expected = (key % 2 == 0).then_some(key as u32);
+            assert_eq!(tree.get(&key)?, expected);
+        }
+        assert_eq!(tree.get(&4)?, Some(40));
+        assert_eq!(tree.get(&5)?, None);
+    }
+    remove_file("set_get_delete")
+}
+
+#[test]
+fn delete_frees_leaves() -> Result<()> {
+    let _ = remove_file("delete_frees_leaves");
+    {
+        let mut tree = Tree::open("delete_frees_leaves")?;
+        for i in 0..2000u64 {
+            tree.set(i, i as u32, SetOpt::UpdateOrInsert)?;
+        }
+        tree.flush()?;
+        let len = std::fs::metadata("delete_frees_leaves")?.len();
+
+        // Empty leaves are removed, And their pages are reused.
+        for i in (0..2000u64).rev().step_by(2).chain((0..2000).step_by(2)) {
+            assert_eq!(tree.delete(&i)?, Some((i, i as u32)));
+        }
+        assert_eq!(tree.get(&10)?, None);
+        for i in 0..2000u64 {
+            tree.set(i, i as u32, SetOpt::UpdateOrInsert)?;
+        }
+        tree.flush()?;
+        assert_eq!(std::fs::metadata("delete_frees_leaves")?.len(), len);
+
+        for i in 500..1500u64 {
+            tree.delete(&i)?;
+        }
+    }
+    {
+        // The free list is stored in the metadata.
+        let mut tree = Tree::open("delete_frees_leaves")?;
+        for i in 0..2000u64 {
+            let expected = (!(500..1500).contains(&i)).then_some(i as u32);
+            assert_eq!(tree.get(&i)?, expected);
+        }
+        let len = std::fs::metadata("delete_frees_leaves")?.len();
+        for i in 500..1500u64 {
+            tree.set(i, i as u32, SetOpt::UpdateOrInsert)?;
+        }
+        tree.flush()?;
+        assert_eq!(std::fs::metadata("delete_frees_leaves")?.len(), len);
+        for i in 0..2000u64 {
+            assert_eq!(tree.get(&i)?, Some(i as u32));
+        }
+    }
+    remove_file("delete_frees_leaves")
+}
+
+#[test]
+fn invalid_key() -> flex_bptree::Result<()> {
+    use flex_bptree::Error;
+    let _ = remove_file("invalid_key");
+    {
+        let mut tree = BPlusTree::<f64, u32, 256>::open("invalid_key")?;
+        tree.set(1.0, 1, SetOpt::UpdateOrInsert)?;
+        let set = tree.set(f64::NAN, 2, SetOpt::UpdateOrInsert);
+        assert!(matches!(set, Err(Error::InvalidKey)));
+        assert!(matches!(tree.get(&f64::NAN), Err(Error::InvalidKey)));
+        assert!(matches!(tree.delete(&f64::NAN), Err(Error::InvalidKey)));
+        assert!(matches!(tree.range(f64::NAN..), Err(Error::InvalidKey)));
+        assert!(matches!(tree.range(..f64::NAN), Err(Error::InvalidKey)));
+        assert_eq!(tree.get(&1.0)?, Some(1));
+    }
+    Ok(remove_file("invalid_key")?)
+}
+
+#[test]
+fn open_guard() -> flex_bptree::Result<()> {
+    use flex_bptree::Error;
+    let _ = remove_file("open_guard");
+    {
+        let _tree = Tree::open("open_guard")?;
+        assert!(matches!(Tree::open("open_guard"), Err(Error::AlreadyOpen)));
+    }
+    // `close` clears the flag too.
+    Tree::open("open_guard")?.close()?;
+    match BPlusTree::<u64, u32, 128>::open("open_guard") {
+        Err(Error::FormatMismatch { expected, found }) => {
+            assert_eq!(expected, "page size 128");
+            assert_eq!(found, "page size 256");
+        }
+        _ => panic!("a different page size is accepted"),
+    }
+    // A file without the magic, Like one that was written by an older version.
+    let mut page = vec![0; 256 * 2];
+    page[..4].copy_from_slice(&[1, 0, 0, 0]);
+    std::fs::write("open_guard", page)?;
+    match Tree::open("open_guard") {
+        Err(Error::FormatMismatch { found, .. }) => {
+            assert_eq!(found, "a file without format version")
+        }
+        _ => panic!("a file without format version is accepted"),
+    }
+    Ok(remove_file("open_guard")?)
+}
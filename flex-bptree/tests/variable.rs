@@ -0,0 +1,37 @@
+use flex_bptree::{BPlusTree, Error, SetOpt};
+use std::{fs::remove_file, io::Result};
+
+type Tree = BPlusTree<String, Vec<u8>, 256>;
+
+#[test]
+fn variable_size() -> Result<()> {
+    let _ = remove_file("variable_size");
+    let key = |i: usize| format!("{}{}", "k".repeat(i % 40), i);
+    {
+        let mut tree = Tree::open("variable_size")?;
+        for i in 0..1000 {
+            assert_eq!(tree.set(key(i), vec![i as u8; i % 60], SetOpt::UpdateOrInsert)?, None);
+        }
+        // Growing a value may overflow the leaf.
+        for i in (0..1000).step_by(7) {
+            let old = tree.set(key(i), vec![1; 70], SetOpt::UpdateOrInsert)?;
+            assert_eq!(old, Some(vec![i as u8; i % 60]));
+        }
+        let set = tree.set("large".into(), vec![0; 200], SetOpt::UpdateOrInsert);
+        assert!(matches!(set, Err(Error::KeyTooLarge)));
+        let set = tree.set(key(1), vec![0; 200], SetOpt::UpdateOrInsert);
+        assert!(matches!(set, Err(Error::KeyTooLarge)));
+        // A key may become a separator key, So it must fit a third of a branch.
+        let set = tree.set("k".repeat(100), vec![], SetOpt::UpdateOrInsert);
+        assert!(matches!(set, Err(Error::KeyTooLarge)));
+        assert_eq!(tree.get(&"large".into())?, None);
+        assert_eq!(tree.get(&key(1))?, Some(vec![1; 1]));
+    }
+    let tree = Tree::open("variable_size")?;
+    for i in 0..1000 {
+        let value = if i % 7 == 0 { vec![1; 70] } else { vec![i as u8; i % 60] };
+        assert_eq!(tree.get(&key(i))?, Some(value));
+    }
+    drop(tree);
+    remove_file("variable_size")
+}
@@ -0,0 +1,210 @@
+use super::*;
+use std::ops::{Bound, RangeBounds};
+
+/// A monoid that summarizes the entries of a subtree.
+///
+/// Its output is stored alongside each child pointer of the branches,
+/// So an aggregate over a key range is answered by combining whole subtrees, See [`BPlusTree::aggregate`].
+///
+/// `combine` must be associative, And `identity` must be its identity element.
+pub trait Aggregate<K, V> {
+	type Output: Key;
+	fn identity() -> Self::Output;
+	fn lift(key: &K, value: &V) -> Self::Output;
+	fn combine(a: Self::Output, b: Self::Output) -> Self::Output;
+}
+
+/// No aggregate, It doesn't take any space in the branches.
+impl<K, V> Aggregate<K, V> for () {
+	type Output = ();
+	fn identity() {}
+	fn lift(_: &K, _: &V) {}
+	fn combine(_: (), _: ()) {}
+}
+
+/// Number of entries.
+pub struct Count;
+
+impl<K, V> Aggregate<K, V> for Count {
+	type Output = u32;
+	fn identity() -> u32 {
+		0
+	}
+	fn lift(_: &K, _: &V) -> u32 {
+		1
+	}
+	/// A tree has `u32::MAX` entries at most, So it doesn't overflow.
+	fn combine(a: u32, b: u32) -> u32 {
+		a.wrapping_add(b)
+	}
+}
+
+/// Sum of values, Computed in a wider type. See [`Summable`].
+pub struct Sum;
+
+impl<K, V: Summable> Aggregate<K, V> for Sum {
+	type Output = V::Sum;
+	fn identity() -> V::Sum {
+		V::Sum::default()
+	}
+	fn lift(_: &K, value: &V) -> V::Sum {
+		value.widen()
+	}
+	fn combine(a: V::Sum, b: V::Sum) -> V::Sum {
+		V::add(a, b)
+	}
+}
+
+/// A value that can be summed by [`Sum`].
+///
+/// Values are summed in a wider type, So the sum of `u32::MAX` entries (the most a tree can have) doesn't overflow.
+/// Except for 128-bit integers, Whose sum wraps around.
+pub trait Summable: Key {
+	type Sum: Key + Default;
+	fn widen(self) -> Self::Sum;
+	fn add(a: Self::Sum, b: Self::Sum) -> Self::Sum;
+}
+
+macro_rules! impl_summable_for {
+    [$($ty:ty => $sum:ty)*] => ($(
+        impl Summable for $ty {
+            type Sum = $sum;
+            fn widen(self) -> $sum { self as $sum }
+            fn add(a: $sum, b: $sum) -> $sum { a.wrapping_add(b) }
+        }
+    )*);
+}
+impl_summable_for!(u8 => u64 u16 => u64 u32 => u64 u64 => u128 u128 => u128 i8 => i64 i16 => i64 i32 => i64 i64 => i128 i128 => i128);
+
+impl Summable for f32 {
+	type Sum = f64;
+	fn widen(self) -> f64 {
+		self as f64
+	}
+	fn add(a: f64, b: f64) -> f64 {
+		a + b
+	}
+}
+
+impl Summable for f64 {
+	type Sum = f64;
+	fn widen(self) -> f64 {
+		self
+	}
+	fn add(a: f64, b: f64) -> f64 {
+		a + b
+	}
+}
+
+/// Minimum value, `None` if there is no entry.
+pub struct Min;
+
+impl<K, V: Key> Aggregate<K, V> for Min {
+	type Output = Option<V>;
+	fn identity() -> Option<V> {
+		None
+	}
+	fn lift(_: &K, value: &V) -> Option<V> {
+		Some(*value)
+	}
+	fn combine(a: Option<V>, b: Option<V>) -> Option<V> {
+		match (a, b) {
+			(Some(a), Some(b)) => Some(if b < a { b } else { a }),
+			(a, b) => a.or(b),
+		}
+	}
+}
+
+/// Maximum value, `None` if there is no entry.
+pub struct Max;
+
+impl<K, V: Key> Aggregate<K, V> for Max {
+	type Output = Option<V>;
+	fn identity() -> Option<V> {
+		None
+	}
+	fn lift(_: &K, value: &V) -> Option<V> {
+		Some(*value)
+	}
+	fn combine(a: Option<V>, b: Option<V>) -> Option<V> {
+		match (a, b) {
+			(Some(a), Some(b)) => Some(if b > a { b } else { a }),
+			(a, b) => a.or(b),
+		}
+	}
+}
+
+impl<K: Key, V: Key, const SIZE: usize, A: Aggregate<K, V>> BPlusTree<K, V, SIZE, A> {
+	/// #### _Blocking_
+	///
+	/// Combine the entries within the `range`.
+	/// Subtrees that are fully covered by the range are not visited, Their stored aggregate is used instead.
+	pub fn aggregate(&self, range: impl RangeBounds<K>) -> Result<A::Output> {
+		ensure_bounds(&range)?;
+		self._aggregate(self.root, &range, None, None)
+	}
+
+	/// Keys of the subtree are within `lower..upper`, `None` means unbounded.
+	fn _aggregate(
+		&self,
+		num: u16,
+		range: &impl RangeBounds<K>,
+		lower: Option<K>,
+		upper: Option<K>,
+	) -> Result<A::Output> {
+		Ok(match self.node(num)? {
+			Node::Leaf(leaf) => leaf
+				.entries
+				.iter()
+				.filter(|(k, _)| range.contains(k))
+				.fold(A::identity(), |acc, (k, v)| A::combine(acc, A::lift(k, v))),
+			Node::Branch(branch) => {
+				let mut acc = A::identity();
+				for (i, &child) in branch.childs.iter().enumerate() {
+					let lower = if i == 0 { lower } else { Some(branch.keys[i - 1]) };
+					let upper = branch.keys.get(i).copied().or(upper);
+					if is_disjoint(range, lower, upper) {
+						continue;
+					}
+					let agg = if covers(range, lower, upper) {
+						branch.aggs[i]
+					} else {
+						self._aggregate(child, range, lower, upper)?
+					};
+					acc = A::combine(acc, agg);
+				}
+				acc
+			}
+		})
+	}
+}
+
+/// Whether the `range` contains every key of a subtree, Whose keys are within `lower..upper`.
+pub(crate) fn covers<K: Key>(range: &impl RangeBounds<K>, lower: Option<K>, upper: Option<K>) -> bool {
+	let start = match (range.start_bound(), lower) {
+		(Bound::Unbounded, _) => true,
+		(Bound::Included(s), Some(l)) => *s <= l,
+		(Bound::Excluded(s), Some(l)) => *s < l,
+		(_, None) => false,
+	};
+	let end = match (range.end_bound(), upper) {
+		(Bound::Unbounded, _) => true,
+		(Bound::Included(e), Some(u)) | (Bound::Excluded(e), Some(u)) => u <= *e,
+		(_, None) => false,
+	};
+	start && end
+}
+
+/// Whether the `range` doesn't contain any key of a subtree, Whose keys are within `lower..upper`.
+pub(crate) fn is_disjoint<K: Key>(range: &impl RangeBounds<K>, lower: Option<K>, upper: Option<K>) -> bool {
+	let before = match (range.start_bound(), upper) {
+		(Bound::Included(s), Some(u)) | (Bound::Excluded(s), Some(u)) => u <= *s,
+		_ => false,
+	};
+	let after = match (range.end_bound(), lower) {
+		(Bound::Included(e), Some(l)) => *e < l,
+		(Bound::Excluded(e), Some(l)) => *e <= l,
+		_ => false,
+	};
+	before || after
+}
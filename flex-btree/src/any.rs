@@ -0,0 +1,164 @@
+use super::*;
+use std::io::Read;
+use std::ops::RangeBounds;
+
+/// Page sizes, That [`AnyTree`] can work with.
+///
+/// Nodes are encoded with the page size as a const generic (`SIZE` of [`BPlusTree`]),
+/// So every size that is opened at runtime needs its own variant. Only these sizes are supported,
+/// A file with another page size can still be opened with [`BPlusTree`], If its size is known at compile time.
+pub const PAGE_SIZES: [usize; 7] = [512, 1024, 2048, 4096, 8192, 16384, 32768];
+
+/// A [`BPlusTree`] whose page size is only known at runtime, Read from the header of the file.
+///
+/// Only the page sizes of [`PAGE_SIZES`] are supported, Other sizes are rejected with [`Error::FormatMismatch`].
+///
+/// Common operations are forwarded to the tree. For the rest (like [`BPlusTree::get`]), Match on the variant.
+pub enum AnyTree<K, V, A = ()> {
+	P512(BPlusTree<K, V, 512, A>),
+	P1024(BPlusTree<K, V, 1024, A>),
+	P2048(BPlusTree<K, V, 2048, A>),
+	P4096(BPlusTree<K, V, 4096, A>),
+	P8192(BPlusTree<K, V, 8192, A>),
+	P16384(BPlusTree<K, V, 16384, A>),
+	P32768(BPlusTree<K, V, 32768, A>),
+}
+
+macro_rules! dispatch {
+	($any: expr, $tree: ident => $body: expr) => {
+		match $any {
+			AnyTree::P512($tree) => $body,
+			AnyTree::P1024($tree) => $body,
+			AnyTree::P2048($tree) => $body,
+			AnyTree::P4096($tree) => $body,
+			AnyTree::P8192($tree) => $body,
+			AnyTree::P16384($tree) => $body,
+			AnyTree::P32768($tree) => $body,
+		}
+	};
+}
+
+/// #### _Blocking_
+///
+/// Read the page size from the header of the file.
+///
+/// It is any size that a [`BPlusTree`] is created with, So it may not be one of [`PAGE_SIZES`].
+/// [`AnyTree`] can open the file, Only if it is.
+pub fn page_size(path: impl AsRef<Path>) -> Result<usize> {
+	let mut bytes = [0; MetaInfo::SIZE];
+	File::open(path)?.read_exact(&mut bytes)?;
+	Ok(MetaInfo::from_bytes(&bytes)?.block_size())
+}
+
+/// #### _Blocking_
+///
+/// Rewrite the tree at `src` into a new file at `dst`, With `page_size`.
+/// The entries are appended leaf by leaf, So the new tree is packed up to its fill factor.
+pub fn convert_page_size<K: Key, V: Key, A: Aggregate<K, V>>(
+	src: impl AsRef<Path>,
+	dst: impl AsRef<Path>,
+	page_size: usize,
+) -> Result<()> {
+	let from = AnyTree::<K, V, A>::open(src)?;
+	let mut to = AnyTree::<K, V, A>::create(dst, page_size)?;
+	if to.len() != 0 {
+		return Err(Error::InvalidInput("The destination file must be empty".into()));
+	}
+	dispatch!(&from, from => dispatch!(&mut to, to => {
+		to.set_fill_factor(from.fill_factor())?;
+		to.extend_from(from)
+	}))
+}
+
+impl<K: Key, V: Key, A: Aggregate<K, V>> AnyTree<K, V, A> {
+	/// #### _Blocking_
+	///
+	/// Open an existing file, With the page size of its header.
+	///
+	/// Returns [`Error::FormatMismatch`], If the page size isn't one of [`PAGE_SIZES`].
+	pub fn open(path: impl AsRef<Path>) -> Result<Self> {
+		let page_size = page_size(&path)?;
+		Self::create(path, page_size)
+	}
+
+	/// #### _Blocking_
+	///
+	/// Same as [`AnyTree::open`], But a new file is created with `page_size`.
+	///
+	/// Returns [`Error::FormatMismatch`], If `page_size` isn't one of [`PAGE_SIZES`].
+	pub fn create(path: impl AsRef<Path>, page_size: usize) -> Result<Self> {
+		Ok(match page_size {
+			512 => Self::P512(BPlusTree::open(path)?),
+			1024 => Self::P1024(BPlusTree::open(path)?),
+			2048 => Self::P2048(BPlusTree::open(path)?),
+			4096 => Self::P4096(BPlusTree::open(path)?),
+			8192 => Self::P8192(BPlusTree::open(path)?),
+			16384 => Self::P16384(BPlusTree::open(path)?),
+			32768 => Self::P32768(BPlusTree::open(path)?),
+			_ => {
+				return Err(Error::FormatMismatch {
+					expected: format!("one of the page sizes {:?}", PAGE_SIZES),
+					found: page_size.to_string(),
+				})
+			}
+		})
+	}
+
+	pub fn page_size(&self) -> usize {
+		dispatch!(self, tree => tree.page_size())
+	}
+
+	pub fn len(&self) -> u32 {
+		dispatch!(self, tree => tree.len())
+	}
+
+	/// #### _Blocking_
+	pub fn set(&mut self, key: K, value: V, opt: SetOption) -> Result<Option<V>> {
+		dispatch!(self, tree => tree.set(key, value, opt))
+	}
+
+	/// #### _Blocking_
+	pub fn delete(&mut self, key: &K) -> Result<Option<(K, V)>> {
+		dispatch!(self, tree => tree.delete(key))
+	}
+
+	/// #### _Blocking_
+	pub fn get_many(&self, keys: &[K]) -> Result<Vec<Option<V>>> {
+		dispatch!(self, tree => tree.get_many(keys))
+	}
+
+	/// #### _Blocking_
+	pub fn nth(&self, index: u32) -> Result<Option<(K, V)>> {
+		dispatch!(self, tree => tree.nth(index))
+	}
+
+	/// #### _Blocking_
+	pub fn rank(&self, key: &K) -> Result<u32> {
+		dispatch!(self, tree => tree.rank(key))
+	}
+
+	/// #### _Blocking_
+	pub fn count_range(&self, range: impl RangeBounds<K>) -> Result<u32> {
+		dispatch!(self, tree => tree.count_range(range))
+	}
+
+	/// #### _Blocking_
+	pub fn aggregate(&self, range: impl RangeBounds<K>) -> Result<A::Output> {
+		dispatch!(self, tree => tree.aggregate(range))
+	}
+
+	/// #### _Blocking_
+	pub fn delete_range(&mut self, range: impl RangeBounds<K>) -> Result<u32> {
+		dispatch!(self, tree => tree.delete_range(range))
+	}
+
+	/// #### _Blocking_
+	pub fn verify(&self) -> Result<Report<K>> {
+		dispatch!(self, tree => tree.verify())
+	}
+
+	/// #### _Blocking_
+	pub fn stats(&self) -> Result<Stats> {
+		dispatch!(self, tree => tree.stats())
+	}
+}
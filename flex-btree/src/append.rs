@@ -0,0 +1,346 @@
+use super::*;
+
+impl<K: Key, V: Key, const SIZE: usize, A: Aggregate<K, V>> BPlusTree<K, V, SIZE, A> {
+	/// #### _Blocking_
+	///
+	/// Move the entries whose key is greater than or equal to `key` into a new tree at `path`, And return it.
+	///
+	/// Only the entries of the leaf that contains `key` are moved one by one. The subtrees on the right of its path
+	/// are copied page by page (without decoding their entries), And attached to the right edge of the new tree.
+	/// So only the nodes on both edges are rebuilt, But every moved page is still written once, As the new tree is another file.
+	///
+	/// This tree is cut in `O(height)` page rewrites, Plus a write for each freed page (see [`BPlusTree::delete_range`]).
+	pub fn split_off(&mut self, key: &K, path: impl AsRef<Path>) -> Result<Self> {
+		ensure_ord(key)?;
+		let mut right = Self::open(path)?;
+		if right.len != 0 {
+			return Err(Error::InvalidInput("The file of the split tree must be empty".into()));
+		}
+		// Path from the root to the leaf that contains `key`.
+		let mut path = Vec::new();
+		let mut num = self.root;
+		let leaf = loop {
+			match self.node(num)? {
+				Node::Branch(branch) => {
+					let index = branch.lookup(key);
+					num = branch.childs[index];
+					path.push((branch, index));
+				}
+				Node::Leaf(leaf) => break leaf,
+			}
+		};
+		let mut spine = right.spine()?;
+		for &(k, v) in leaf.entries.iter().filter(|(k, _)| k >= key) {
+			spine.push(&mut right, k, v)?;
+		}
+		// Right siblings of the path, From the bottom level. So every subtree is higher than the ones before it.
+		for (level, (branch, index)) in path.iter().rev().enumerate() {
+			for i in index + 1..branch.childs.len() {
+				let child = spine.copy(&mut right, self, branch.childs[i])?;
+				spine.attach(&mut right, level, branch.keys[i - 1], child, (branch.counts[i], branch.aggs[i]))?;
+			}
+		}
+		spine.finish(&mut right)?;
+		self.delete_range(*key..)?;
+		Ok(right)
+	}
+
+	/// #### _Blocking_
+	///
+	/// Move every entry of `other` into this tree, Leaving `other` empty.
+	/// Keys of `other` must be greater than the keys of this tree, Its page size may be different.
+	///
+	/// See [`BPlusTree::split_off`], If both trees have the same page size: The subtrees of `other` are attached
+	/// to the right edge of this tree. Otherwise (or if this tree has watchers) the entries are appended one by one.
+	pub fn append<const OTHER: usize>(&mut self, other: &mut BPlusTree<K, V, OTHER, A>) -> Result<()> {
+		// Counted descents find the smallest and largest keys, Even if an edge leaf is empty.
+		let first = match other.nth(0)? {
+			Some((key, _)) => key,
+			None => return Ok(()),
+		};
+		let last = match self.len() {
+			0 => None,
+			len => self.nth(len - 1)?,
+		};
+		if matches!(last, Some((last, _)) if first <= last) {
+			return Err(Error::InvalidKey);
+		}
+		self.extend_from(other)?;
+		other.clear()
+	}
+
+	/// Append every entry of `other` to the right edge of this tree.
+	pub(crate) fn extend_from<const OTHER: usize>(&mut self, other: &BPlusTree<K, V, OTHER, A>) -> Result<()> {
+		let first = match other.nth(0)? {
+			Some((key, _)) => key,
+			None => return Ok(()),
+		};
+		let mut spine = self.spine()?;
+		// Watchers are notified of every entry, So the entries have to be visited.
+		if OTHER == SIZE && self.watchers.is_empty() {
+			// A tree lower than this one is attached as a whole, Otherwise its subtrees of the same height.
+			let level = spine.branches.len().min(other.height()?);
+			for (key, num, total) in other.subtrees(first, level)? {
+				let child = spine.copy(self, other, num)?;
+				spine.attach(self, level, key, child, total)?;
+			}
+			return spine.finish(self);
+		}
+		let mut view = other.get(Get::First)?;
+		loop {
+			for &(k, v) in view.iter() {
+				spine.push(self, k, v)?;
+			}
+			if !view.next()? {
+				break;
+			}
+		}
+		spine.finish(self)
+	}
+
+	/// Load the right most path of the tree.
+	fn spine(&self) -> Result<Spine<K, V, A::Output, SIZE>> {
+		let mut branches = Vec::new();
+		let mut num = self.root;
+		loop {
+			match self.node(num)? {
+				Node::Branch(branch) => {
+					let child = *branch.childs.last().unwrap();
+					branches.push((num, branch));
+					num = child;
+				}
+				Node::Leaf(leaf) => {
+					branches.reverse();
+					return Ok(Spine { leaf: (num, leaf), last: None, branches });
+				}
+			}
+		}
+	}
+
+	/// Number of branch levels, `0` if the root is a leaf.
+	fn height(&self) -> Result<usize> {
+		let mut height = 0;
+		let mut num = self.root;
+		while let Node::Branch(branch) = self.node(num)? {
+			num = branch.childs[0];
+			height += 1;
+		}
+		Ok(height)
+	}
+
+	/// Subtrees at the `level` (from the bottom), In order. With their lower bound, Number of entries and aggregate.
+	/// `first` is the first key of the tree.
+	fn subtrees(&self, first: K, level: usize) -> Result<Vec<(K, u16, (u32, A::Output))>> {
+		let agg = match self.node(self.root)? {
+			Node::Branch(branch) => branch.aggregate::<V, A>(),
+			Node::Leaf(leaf) => leaf.aggregate::<A>(),
+		};
+		let mut nodes = vec![(first, self.root, (self.len, agg))];
+		for _ in level..self.height()? {
+			let mut next = Vec::new();
+			for (lower, num, _) in nodes {
+				if let Node::Branch(branch) = self.node(num)? {
+					for (i, &child) in branch.childs.iter().enumerate() {
+						let key = if i == 0 { lower } else { branch.keys[i - 1] };
+						next.push((key, child, (branch.counts[i], branch.aggs[i])));
+					}
+				}
+			}
+			nodes = next;
+		}
+		Ok(nodes)
+	}
+}
+
+/// The right most path of a tree, Kept in memory while entries are appended to it.
+///
+/// New leaves (and branches) are started when the last one is filled up to the fill factor,
+/// So nodes are packed instead of being splitted at the middle.
+struct Spine<K, V, S, const SIZE: usize> {
+	leaf: (u16, Leaf<K, V, SIZE>),
+	/// Last leaf that is copied by [`Spine::copy`], It is written when the next leaf is copied (or by `finish`).
+	/// So it is the right most leaf, Instead of `leaf`.
+	last: Option<(u16, [u8; SIZE])>,
+	/// From the bottom level to the root.
+	branches: Vec<(u16, Branch<K, S, SIZE>)>,
+}
+
+impl<K: Key, V: Key, S: Key, const SIZE: usize> Spine<K, V, S, SIZE> {
+	/// `key` must be greater than every key of the tree.
+	fn push<A: Aggregate<K, V, Output = S>>(&mut self, tree: &mut BPlusTree<K, V, SIZE, A>, key: K, value: V) -> Result<()> {
+		if self.leaf.1.entries.len() >= tree.fill(Leaf::<K, V, SIZE>::capacity()) {
+			let mut leaf = Leaf::new();
+			leaf.prev = self.leaf.0;
+			let num = tree.create_page(leaf.to_bytes())?;
+			self.leaf.1.next = num;
+			tree.write(self.leaf.0, self.leaf.1.to_bytes())?;
+			let left = (self.leaf.1.entries.len() as u32, self.leaf.1.aggregate::<A>());
+			self.leaf = (num, leaf);
+			self.add_child(tree, 0, key, num, left)?;
+		}
+		self.leaf.1.entries.push((key, value));
+		let agg = A::lift(&key, &value);
+		for (_, branch) in self.branches.iter_mut() {
+			*branch.counts.last_mut().unwrap() += 1;
+			let last = branch.aggs.last_mut().unwrap();
+			*last = A::combine(*last, agg);
+		}
+		tree.len += 1;
+		tree.notify(Event::Inserted { key, new: value });
+		Ok(())
+	}
+
+	/// Add a new node `num` to the branch at `level`, As its last child.
+	/// `left` is the number of entries and aggregate of the node on its left, That is used if a new root is needed.
+	fn add_child<A: Aggregate<K, V, Output = S>>(
+		&mut self,
+		tree: &mut BPlusTree<K, V, SIZE, A>,
+		level: usize,
+		key: K,
+		num: u16,
+		left: (u32, S),
+	) -> Result<()> {
+		if level == self.branches.len() {
+			let root = Branch::create_root(key, (tree.root, left.0, left.1), (num, 0, A::identity()));
+			tree.root = tree.create_page(root.to_bytes())?;
+			self.branches.push((tree.root, root));
+			return Ok(());
+		}
+		let (branch_num, branch) = &mut self.branches[level];
+		if branch.childs.len() < tree.fill(Branch::<K, S, SIZE>::capacity()) {
+			branch.keys.push(key);
+			branch.childs.push(num);
+			branch.counts.push(0);
+			branch.aggs.push(A::identity());
+			return Ok(());
+		}
+		let mut other = Branch::new();
+		other.childs.push(num);
+		other.counts.push(0);
+		other.aggs.push(A::identity());
+		let other_num = tree.create_page(other.to_bytes())?;
+		tree.write(*branch_num, branch.to_bytes())?;
+		let left = (branch.count(), branch.aggregate::<V, A>());
+		self.branches[level] = (other_num, other);
+		self.add_child(tree, level + 1, key, other_num, left)
+	}
+
+	/// Copy the subtree `num` of `src` page by page, And returns its page number in the `tree`.
+	///
+	/// Entries of the leaves aren't decoded, Only their links are changed. So are the page numbers of the childs of the branches.
+	fn copy<A: Aggregate<K, V, Output = S>, const OTHER: usize>(
+		&mut self,
+		tree: &mut BPlusTree<K, V, SIZE, A>,
+		src: &BPlusTree<K, V, OTHER, A>,
+		num: u16,
+	) -> Result<u16> {
+		let mut bytes = [0; SIZE];
+		bytes.copy_from_slice(&src.read(num)?);
+		if let NodeRef::Branch(_) = tree.node_ref(num, &bytes)? {
+			let mut branch = Branch::<K, S, SIZE>::from_bytes(bytes);
+			for i in 0..branch.childs.len() {
+				branch.childs[i] = self.copy(tree, src, branch.childs[i])?;
+			}
+			return tree.create_page(branch.to_bytes());
+		}
+		let new = tree.alloc_page()?;
+		let prev = match self.last.take() {
+			Some((prev, mut page)) => {
+				link(&mut page, new, None);
+				tree.write(prev, page)?;
+				prev
+			}
+			None => {
+				self.leaf.1.next = new;
+				tree.write(self.leaf.0, self.leaf.1.to_bytes())?;
+				self.leaf.0
+			}
+		};
+		link(&mut bytes, 0, Some(prev));
+		self.last = Some((new, bytes));
+		Ok(new)
+	}
+
+	/// Add the subtree `num` (that is copied) as the last child of the branch at `level`, `level` is also its height.
+	/// `key` is its lower bound, And `total` is its number of entries and aggregate.
+	fn attach<A: Aggregate<K, V, Output = S>>(
+		&mut self,
+		tree: &mut BPlusTree<K, V, SIZE, A>,
+		level: usize,
+		key: K,
+		num: u16,
+		total: (u32, S),
+	) -> Result<()> {
+		// The tree is lower than the subtree, So single child roots are added. They are fixed by `finish`.
+		while self.branches.len() < level {
+			let mut root = Branch::new();
+			root.childs.push(tree.root);
+			root.counts.push(tree.len);
+			root.aggs.push(self.aggregate::<A>());
+			tree.root = tree.create_page(root.to_bytes())?;
+			self.branches.push((tree.root, root));
+		}
+		let left = (tree.len, self.aggregate::<A>());
+		self.add_child(tree, level, key, num, left)?;
+		for (_, branch) in self.branches[level..].iter_mut() {
+			*branch.counts.last_mut().unwrap() += total.0;
+			let last = branch.aggs.last_mut().unwrap();
+			*last = A::combine(*last, total.1);
+		}
+		tree.len += total.0;
+		Ok(())
+	}
+
+	/// Aggregate of the whole tree.
+	fn aggregate<A: Aggregate<K, V, Output = S>>(&self) -> S {
+		match self.branches.last() {
+			Some((_, root)) => root.aggregate::<V, A>(),
+			None => self.leaf.1.aggregate::<A>(),
+		}
+	}
+
+	/// Write the spine, And fix the last nodes of each level that may be underflowed.
+	///
+	/// The first nodes of each level are fixed too, As an empty tree (or the leaf that is splitted by `split_off`)
+	/// leaves them underflowed when subtrees are attached.
+	fn finish<A: Aggregate<K, V, Output = S>>(mut self, tree: &mut BPlusTree<K, V, SIZE, A>) -> Result<()> {
+		tree.unpin();
+		tree.write(self.leaf.0, self.leaf.1.to_bytes())?;
+		if let Some((num, mut page)) = self.last.take() {
+			link(&mut page, 0, None);
+			tree.write(num, page)?;
+		}
+		for (num, branch) in self.branches.iter_mut() {
+			tree.write(*num, branch.to_bytes())?;
+		}
+		// Left most path, From the bottom level.
+		let mut left = Vec::new();
+		let mut num = tree.root;
+		while let Node::Branch(branch) = tree.node(num)? {
+			left.push(num);
+			num = branch.childs[0];
+		}
+		left.reverse();
+		for (level, (num, branch)) in self.branches.iter_mut().enumerate() {
+			if let Some(&first) = left.get(level).filter(|&&first| first != *num) {
+				if let Node::Branch(mut first_branch) = tree.node(first)? {
+					tree.fix_childs(&mut first_branch)?;
+					tree.write(first, first_branch.to_bytes())?;
+				}
+			}
+			tree.fix_childs(branch)?;
+			tree.write(*num, branch.to_bytes())?;
+		}
+		tree.shrink()
+	}
+}
+
+/// Set the links of a leaf, In its raw bytes.
+///
+/// Leaf layout: Node type (1) + next (2) + prev (2) + ...
+fn link<const SIZE: usize>(page: &mut [u8; SIZE], next: u16, prev: Option<u16>) {
+	page[1..3].copy_from_slice(&next.to_le_bytes());
+	if let Some(prev) = prev {
+		page[3..5].copy_from_slice(&prev.to_le_bytes());
+	}
+}
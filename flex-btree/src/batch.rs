@@ -0,0 +1,50 @@
+use super::*;
+
+impl<K: Key, V: Key, const SIZE: usize, A: Aggregate<K, V>> BPlusTree<K, V, SIZE, A> {
+	/// #### _Blocking_
+	///
+	/// Look up many keys at once, Returns their values in the same order as `keys`.
+	///
+	/// The keys are sorted and a descent is shared by every key of the same subtree,
+	/// So each page is read at most once per batch.
+	///
+	/// Returns [`Error::InvalidKey`], If any key is `NaN`.
+	pub fn get_many(&self, keys: &[K]) -> Result<Vec<Option<V>>> {
+		keys.iter().try_for_each(ensure_ord)?;
+		let mut probes: Vec<usize> = (0..keys.len()).collect();
+		probes.sort_by(|&a, &b| keys[a].partial_cmp(&keys[b]).expect("Key can't be `NaN`"));
+		let mut values = vec![None; keys.len()];
+		if !probes.is_empty() {
+			self._get_many(self.root, keys, &probes, &mut values)?;
+		}
+		Ok(values)
+	}
+
+	/// `probes` are indexes of `keys` in sorted order.
+	fn _get_many(&self, num: u16, keys: &[K], probes: &[usize], values: &mut [Option<V>]) -> Result<()> {
+		let bytes = self.read(num)?;
+		match self.node_ref(num, &bytes)? {
+			NodeRef::Leaf(leaf) => {
+				for &i in probes {
+					values[i] = leaf.get(&keys[i]);
+				}
+			}
+			NodeRef::Branch(branch) => {
+				let mut rest = probes;
+				while let Some(&first) = rest.first() {
+					let index = branch.lookup(&keys[first]);
+					// Probes that belong to the same child.
+					let len = if index + 1 < branch.len() {
+						let upper = branch.key(index);
+						rest.iter().take_while(|&&i| keys[i] < upper).count()
+					} else {
+						rest.len()
+					};
+					self._get_many(branch.child(index), keys, &rest[..len], values)?;
+					rest = &rest[len..];
+				}
+			}
+		}
+		Ok(())
+	}
+}
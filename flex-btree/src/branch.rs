@@ -1,33 +1,41 @@
 use bytes::{Buf, BufMut};
+use std::cmp::Ordering;
+use std::marker::PhantomData;
+use std::mem::replace;
 
+use crate::aggregate::Aggregate;
 use crate::entry::Key;
 
-pub struct Branch<K, const SIZE: usize> {
+pub struct Branch<K, S, const SIZE: usize> {
     pub keys: Vec<K>,
     pub childs: Vec<u16>,
+    /// Number of entries in the subtree of each child.
+    pub counts: Vec<u32>,
+    /// Aggregate of the subtree of each child, See [`Aggregate`].
+    pub aggs: Vec<S>,
 }
 
-pub enum DeleteOperation<K> {
-    UpdateIdx(K),
-    ReplaceIdx(K),
-    RemoveIdx,
-    Nothing,
-}
-
-impl<K: Key, const SIZE: usize> Branch<K, SIZE> {
+impl<K: Key, S: Key, const SIZE: usize> Branch<K, S, SIZE> {
     pub fn capacity() -> usize {
         // BlockSize - (Node type (1) + keys len (2))
-        (SIZE - 3) / (K::SIZE + 2)
+        // Each child has a page number (2), a subtree count (4) and an aggregate
+        (SIZE - 3) / (K::SIZE + 2 + 4 + S::SIZE)
     }
 
     pub fn is_full(&self) -> bool {
         self.childs.len() >= Self::capacity()
     }
 
+    pub fn is_underflow(&self) -> bool {
+        self.childs.len() < Self::capacity() / 2
+    }
+
     pub fn new() -> Self {
         Self {
             keys: Vec::with_capacity(Self::capacity() - 1),
             childs: Vec::with_capacity(Self::capacity()),
+            counts: Vec::with_capacity(Self::capacity()),
+            aggs: Vec::with_capacity(Self::capacity()),
         }
     }
 
@@ -36,11 +44,13 @@ impl<K: Key, const SIZE: usize> Branch<K, SIZE> {
         let mut view = buf.as_mut();
         // Node type
         view.put_u8(1);
-        // We don't need to write the `childs  length,
+        // We don't need to write the `childs`, `counts` and `aggs` length,
         // because it's always the same as the `keys` length + 1.
         view.put_u16_le(self.keys.len() as u16);
         self.keys.iter().for_each(|k| view.put(&k.to_bytes()[..]));
         self.childs.iter().for_each(|&c| view.put_u16_le(c));
+        self.counts.iter().for_each(|&c| view.put_u32_le(c));
+        self.aggs.iter().for_each(|&s| view.put(&s.to_bytes()[..]));
         buf
     }
 
@@ -56,30 +66,53 @@ impl<K: Key, const SIZE: usize> Branch<K, SIZE> {
         for _ in 0..keys_len + 1 {
             this.childs.push(view.get_u16_le());
         }
+        for _ in 0..keys_len + 1 {
+            this.counts.push(view.get_u32_le());
+        }
+        for _ in 0..keys_len + 1 {
+            this.aggs.push(S::from_bytes(&view.copy_to_bytes(S::SIZE)));
+        }
         this
     }
 
+    /// Total number of entries in this subtree.
+    pub fn count(&self) -> u32 {
+        self.counts.iter().sum()
+    }
+
+    /// Aggregate of this subtree.
+    pub fn aggregate<V, A: Aggregate<K, V, Output = S>>(&self) -> S {
+        self.aggs.iter().fold(A::identity(), |acc, &s| A::combine(acc, s))
+    }
+
     /// # Panic
     /// Panic if `childs` is empty,
     /// Make sure that `childs` has at least one element.
-    pub fn insert(&mut self, index: usize, (k, n): (K, u16)) {
+    pub fn insert(&mut self, index: usize, (k, n, count, agg): (K, u16, u32, S)) {
         self.keys.insert(index, k);
         self.childs.insert(index + 1, n);
+        self.counts.insert(index + 1, count);
+        self.aggs.insert(index + 1, agg);
     }
 
-    pub fn get_key_at(&mut self, lookup_idx: usize) -> &mut K {
-        if lookup_idx == 0 {
-            &mut self.keys[0]
-        } else {
-            &mut self.keys[lookup_idx - 1]
-        }
+    /// Remove the child at `index` and its separator key,
+    /// Its entries are expected to be merged into the left child.
+    ///
+    /// The caller is responsible for updating the aggregate of the left child.
+    pub fn remove_child(&mut self, index: usize) -> u16 {
+        self.keys.remove(index - 1);
+        let count = self.counts.remove(index);
+        self.counts[index - 1] += count;
+        self.aggs.remove(index);
+        self.childs.remove(index)
     }
-    pub fn remove_key_at(&mut self, lookup_idx: usize) -> K {
-        if lookup_idx == 0 {
-            self.keys.remove(0)
-        } else {
-            self.keys.remove(lookup_idx - 1)
-        }
+
+    /// Remove the child at `index` with its entries, And a separator key next to it.
+    pub fn drop_child(&mut self, index: usize) -> u16 {
+        self.keys.remove(index.saturating_sub(1));
+        self.counts.remove(index);
+        self.aggs.remove(index);
+        self.childs.remove(index)
     }
 
     pub fn lookup(&self, key: &K) -> usize {
@@ -94,41 +127,123 @@ impl<K: Key, const SIZE: usize> Branch<K, SIZE> {
             .binary_search_by(|k| k.partial_cmp(key).expect("Key can't be `NaN`"))
     }
 
-    pub fn create_root(key: K, left: u16, right: u16) -> Self {
+    pub fn create_root(key: K, left: (u16, u32, S), right: (u16, u32, S)) -> Self {
         let mut branch = Self::new();
         branch.keys.push(key);
-        branch.childs.push(left);
-        branch.childs.push(right);
+        for (child, count, agg) in [left, right] {
+            branch.childs.push(child);
+            branch.counts.push(count);
+            branch.aggs.push(agg);
+        }
         branch
     }
 
     /// This function splits `Self` at the middle, and returns the other half. with reminder key.
     pub fn split_at_mid(&mut self) -> (Self, K) {
-        let mid = self.childs.len() / 2;
+        self.split_at(self.childs.len() / 2)
+    }
+
+    /// Split `Self` so that it keeps `mid` childs, and returns the other half. with reminder key.
+    pub fn split_at(&mut self, mid: usize) -> (Self, K) {
         let keys = self.keys.drain(mid..).collect::<Vec<_>>();
         let childs = self.childs.drain(mid..).collect::<Vec<_>>();
-        (Self { keys, childs }, self.keys.pop().unwrap())
+        let counts = self.counts.drain(mid..).collect::<Vec<_>>();
+        let aggs = self.aggs.drain(mid..).collect::<Vec<_>>();
+        let other = Self {
+            keys,
+            childs,
+            counts,
+            aggs,
+        };
+        (other, self.keys.pop().unwrap())
+    }
+
+    /// Append the right sibling into `Self`, `key` is their separator key in the parent.
+    pub fn merge(&mut self, key: K, mut right: Self) {
+        self.keys.push(key);
+        self.keys.append(&mut right.keys);
+        self.childs.append(&mut right.childs);
+        self.counts.append(&mut right.counts);
+        self.aggs.append(&mut right.aggs);
+    }
+
+    /// Move childs between `Self` and its right sibling, So both have almost same number of childs.
+    /// `key` is their separator key in the parent, Returns the new separator key.
+    pub fn balance(&mut self, key: K, right: &mut Self) -> K {
+        let mid = (self.childs.len() + right.childs.len()) / 2;
+        self.merge(key, replace(right, Self::new()));
+        let (other, key) = self.split_at(mid);
+        *right = other;
+        key
     }
 
     pub fn child_at(&self, lookup_idx: usize) -> u16 {
         self.childs[lookup_idx]
     }
+}
+
+/// A branch that is borrowed from the raw bytes of its page, Without decoding it.
+pub struct BranchRef<'a, K, S, const SIZE: usize> {
+    bytes: &'a [u8; SIZE],
+    _marker: PhantomData<(K, S)>,
+}
+
+impl<'a, K: Key, S: Key, const SIZE: usize> BranchRef<'a, K, S, SIZE> {
+    // Node type (1) + keys len (2)
+    const HEADER: usize = 3;
+
+    /// Returns `None` if the number of childs exceeds the capacity.
+    pub fn new(bytes: &'a [u8; SIZE]) -> Option<Self> {
+        let this = Self {
+            bytes,
+            _marker: PhantomData,
+        };
+        (this.len() <= Branch::<K, S, SIZE>::capacity()).then(|| this)
+    }
+
+    /// Number of childs.
+    pub fn len(&self) -> usize {
+        u16::from_le_bytes([self.bytes[1], self.bytes[2]]) as usize + 1
+    }
 
-    pub fn sibings_at(&self, lookup_idx: usize) -> (Option<u16>, Option<u16>) {
-        (
-            self.childs.get(lookup_idx - 1).cloned(),
-            self.childs.get(lookup_idx + 1).cloned(),
-        )
+    pub fn key(&self, index: usize) -> K {
+        let offset = Self::HEADER + index * K::SIZE;
+        K::from_bytes(&self.bytes[offset..offset + K::SIZE])
+    }
+
+    pub fn child(&self, index: usize) -> u16 {
+        let offset = Self::HEADER + (self.len() - 1) * K::SIZE + index * 2;
+        u16::from_le_bytes([self.bytes[offset], self.bytes[offset + 1]])
+    }
+
+    pub fn agg(&self, index: usize) -> S {
+        let len = self.len();
+        let offset = Self::HEADER + (len - 1) * K::SIZE + len * (2 + 4) + index * S::SIZE;
+        S::from_bytes(&self.bytes[offset..offset + S::SIZE])
+    }
+
+    /// Same as [`Branch::lookup`], But the keys are decoded in place.
+    pub fn lookup(&self, key: &K) -> usize {
+        let (mut low, mut high) = (0, self.len() - 1);
+        while low < high {
+            let mid = low + (high - low) / 2;
+            match self.key(mid).partial_cmp(key).expect("Key can't be `NaN`") {
+                Ordering::Less => low = mid + 1,
+                Ordering::Greater => high = mid,
+                Ordering::Equal => return mid + 1,
+            }
+        }
+        low
     }
 }
 
 #[cfg(test)]
 mod tests {
-    type Branch = super::Branch<u64, 4096>;
+    type Branch = super::Branch<u64, (), 4096>;
 
     #[test]
     fn check_capacity() {
-        assert_eq!(Branch::capacity(), 409);
+        assert_eq!(Branch::capacity(), 292);
     }
 
     #[test]
@@ -154,28 +269,75 @@ mod tests {
 
         assert_eq!(branch.keys, branch2.keys);
         assert_eq!(branch.childs, branch2.childs);
+        assert_eq!(branch.counts, branch2.counts);
+        assert_eq!(branch.aggs, branch2.aggs);
     }
 
     #[test]
     fn split_at_mid() {
-        let mut branch = Branch::create_root(0, 0, 1);
+        let mut branch = Branch::create_root(0, (0, 1, ()), (1, 1, ()));
 
-        for i in 1..408 {
-            branch.insert(i, (i as u64, i as u16 + 1));
+        for i in 1..291 {
+            branch.insert(i, (i as u64, i as u16 + 1, 1, ()));
         }
 
         assert!(branch.is_full());
+        assert_eq!(branch.count(), 292);
 
         test_byte_conversion(&branch);
 
         let (other, remainder) = branch.split_at_mid();
 
-        assert_eq!(branch.keys, (0..=202).collect::<Vec<_>>());
-        assert_eq!(branch.childs, (0..=203).collect::<Vec<_>>());
+        assert_eq!(branch.keys, (0..=144).collect::<Vec<_>>());
+        assert_eq!(branch.childs, (0..=145).collect::<Vec<_>>());
+        assert_eq!(branch.count(), 146);
+
+        assert_eq!(remainder, 145);
 
-        assert_eq!(remainder, 203);
+        assert_eq!(other.keys, (146..=290).collect::<Vec<_>>());
+        assert_eq!(other.childs, (146..=291).collect::<Vec<_>>());
+        assert_eq!(other.count(), 146);
+    }
+
+    #[test]
+    fn branch_ref() {
+        let mut branch = super::Branch::<u64, u32, 4096>::create_root(10, (1, 5, 50), (2, 6, 60));
+        branch.insert(1, (20, 3, 7, 70));
 
-        assert_eq!(other.keys, (204..=407).collect::<Vec<_>>());
-        assert_eq!(other.childs, (204..=408).collect::<Vec<_>>());
+        let bytes = branch.to_bytes();
+        let branch_ref = super::BranchRef::<u64, u32, 4096>::new(&bytes).unwrap();
+        assert_eq!(branch_ref.len(), 3);
+        for key in 0..30 {
+            assert_eq!(branch_ref.lookup(&key), branch.lookup(&key));
+        }
+        assert_eq!(
+            (0..3).map(|i| branch_ref.child(i)).collect::<Vec<_>>(),
+            [1, 2, 3]
+        );
+        assert_eq!((0..3).map(|i| branch_ref.agg(i)).collect::<Vec<_>>(), [50, 60, 70]);
+    }
+
+    #[test]
+    fn merge_and_balance() {
+        let mut left = Branch::create_root(10, (1, 5, ()), (2, 5, ()));
+        let mut right = Branch::create_root(40, (3, 5, ()), (4, 5, ()));
+        right.insert(1, (50, 5, 5, ()));
+        right.insert(2, (60, 6, 5, ()));
+
+        let key = left.balance(30, &mut right);
+        assert_eq!(key, 40);
+        assert_eq!(left.keys, [10, 30]);
+        assert_eq!(left.childs, [1, 2, 3]);
+        assert_eq!(right.keys, [50, 60]);
+        assert_eq!(right.childs, [4, 5, 6]);
+
+        left.merge(key, right);
+        assert_eq!(left.keys, [10, 30, 40, 50, 60]);
+        assert_eq!(left.childs, [1, 2, 3, 4, 5, 6]);
+        assert_eq!(left.count(), 30);
+
+        assert_eq!(left.remove_child(1), 2);
+        assert_eq!(left.keys, [30, 40, 50, 60]);
+        assert_eq!(left.counts, [10, 5, 5, 5, 5]);
     }
 }
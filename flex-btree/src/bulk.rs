@@ -0,0 +1,210 @@
+use super::*;
+use crate::aggregate::{covers, is_disjoint};
+use std::ops::{Bound, RangeBounds};
+
+impl<K: Key, V: Key, const SIZE: usize, A: Aggregate<K, V>> BPlusTree<K, V, SIZE, A> {
+	/// #### _Blocking_
+	///
+	/// Remove the entries within the `range`, Returns the number of removed entries.
+	///
+	/// Subtrees that are fully covered by the range are dropped at once and their pages are freed,
+	/// So only the nodes near both boundaries of the range are visited and rebalanced.
+	pub fn delete_range(&mut self, range: impl RangeBounds<K>) -> Result<u32> {
+		ensure_bounds(&range)?;
+		if is_empty(&range) {
+			return Ok(0);
+		}
+		if covers(&range, None, None) {
+			let len = self.len;
+			self.clear()?;
+			return Ok(len);
+		}
+		// Link the leaves that survive on both sides of the range, Before the leaves between them are freed.
+		let (num, leaf, lower, upper) = self.leaf_at(range.start_bound(), false)?;
+		let left = if covers(&range, lower, upper) { leaf.prev } else { num };
+		let (num, leaf, lower, upper) = self.leaf_at(range.end_bound(), true)?;
+		let right = if covers(&range, lower, upper) { leaf.next } else { num };
+		if left != right {
+			self.link(left, right)?;
+		}
+		let mut deleted = Vec::new();
+		let (removed, _) = self._remove(self.root, &range, None, None, &mut |_, _| true, true, &mut deleted)?;
+		self.removed(removed, deleted)
+	}
+
+	/// #### _Blocking_
+	///
+	/// Keep only the entries for which `f` returns `true`, Returns the number of removed entries.
+	pub fn retain(&mut self, mut f: impl FnMut(&K, &V) -> bool) -> Result<u32> {
+		let mut deleted = Vec::new();
+		let (removed, _) = self._remove(self.root, &(..), None, None, &mut |k, v| !f(k, v), false, &mut deleted)?;
+		self.removed(removed, deleted)
+	}
+
+	fn removed(&mut self, removed: u32, mut deleted: Vec<(K, V)>) -> Result<u32> {
+		self.len -= removed;
+		self.shrink()?;
+		deleted.sort_by(|(a, _), (b, _)| a.partial_cmp(b).expect("Key can't be `NaN`"));
+		for (key, old) in deleted {
+			self.notify(Event::Deleted { key, old });
+		}
+		Ok(removed)
+	}
+
+	/// Returns the leaf that contains the `bound`, Its page number and the bounds of its keys.
+	/// `Unbounded` means the first leaf, Or the last one if `last` is `true`.
+	fn leaf_at(&self, bound: Bound<&K>, last: bool) -> Result<(u16, Leaf<K, V, SIZE>, Option<K>, Option<K>)> {
+		let (mut num, mut lower, mut upper) = (self.root, None, None);
+		loop {
+			match self.node(num)? {
+				Node::Branch(branch) => {
+					let index = match bound {
+						Bound::Included(key) | Bound::Excluded(key) => branch.lookup(key),
+						Bound::Unbounded if last => branch.childs.len() - 1,
+						Bound::Unbounded => 0,
+					};
+					if index > 0 {
+						lower = Some(branch.keys[index - 1]);
+					}
+					upper = branch.keys.get(index).copied().or(upper);
+					num = branch.childs[index];
+				}
+				Node::Leaf(leaf) => return Ok((num, leaf, lower, upper)),
+			}
+		}
+	}
+
+	fn link(&mut self, left: u16, right: u16) -> Result<()> {
+		if left != 0 {
+			let mut leaf = Leaf::<K, V, SIZE>::from_bytes(self.read(left)?);
+			leaf.next = right;
+			self.write(left, leaf.to_bytes())?;
+		}
+		if right != 0 {
+			let mut leaf = Leaf::<K, V, SIZE>::from_bytes(self.read(right)?);
+			leaf.prev = left;
+			self.write(right, leaf.to_bytes())?;
+		}
+		Ok(())
+	}
+
+	/// Remove the entries within the `range`, For which `f` returns `true`.
+	/// Keys of the subtree are within `lower..upper`, `None` means unbounded.
+	///
+	/// If `drop` is `true`, Subtrees that are fully covered by the range are freed without visiting their entries.
+	/// Entries that are watched are collected into `deleted`.
+	///
+	/// Returns the number of removed entries and the new aggregate of the node.
+	fn _remove(
+		&mut self,
+		num: u16,
+		range: &impl RangeBounds<K>,
+		lower: Option<K>,
+		upper: Option<K>,
+		f: &mut impl FnMut(&K, &V) -> bool,
+		drop: bool,
+		deleted: &mut Vec<(K, V)>,
+	) -> Result<(u32, A::Output)> {
+		match self.node(num)? {
+			Node::Leaf(mut leaf) => {
+				let len = leaf.entries.len();
+				let (gone, kept) = leaf.entries.into_iter().partition(|(k, v)| range.contains(k) && f(k, v));
+				leaf.entries = kept;
+				if !self.watchers.is_empty() {
+					deleted.extend(gone.into_iter().filter(|(k, _)| self.is_watched(k)));
+				}
+				let removed = (len - leaf.entries.len()) as u32;
+				if removed > 0 {
+					self.write(num, leaf.to_bytes())?;
+				}
+				Ok((removed, leaf.aggregate::<A>()))
+			}
+			Node::Branch(mut branch) => {
+				let bounds: Vec<_> = (0..branch.childs.len())
+					.map(|i| {
+						let lower = if i == 0 { lower } else { Some(branch.keys[i - 1]) };
+						(lower, branch.keys.get(i).copied().or(upper))
+					})
+					.collect();
+
+				let mut removed = 0;
+				// From right to left, So dropping a child doesn't shift the childs that are not visited yet.
+				for (i, &(lower, upper)) in bounds.iter().enumerate().rev() {
+					if is_disjoint(range, lower, upper) {
+						continue;
+					}
+					if drop && covers(range, lower, upper) {
+						removed += branch.counts[i];
+						let child = branch.drop_child(i);
+						self.free_subtree(child, deleted)?;
+					} else {
+						let (n, agg) = self._remove(branch.childs[i], range, lower, upper, f, drop, deleted)?;
+						removed += n;
+						branch.counts[i] -= n;
+						branch.aggs[i] = agg;
+					}
+				}
+				if removed > 0 {
+					self.fix_childs(&mut branch)?;
+					self.write(num, branch.to_bytes())?;
+				}
+				Ok((removed, branch.aggregate::<V, A>()))
+			}
+		}
+	}
+
+	fn free_subtree(&mut self, num: u16, deleted: &mut Vec<(K, V)>) -> Result<()> {
+		match self.node(num)? {
+			Node::Branch(branch) => {
+				for child in branch.childs {
+					self.free_subtree(child, deleted)?;
+				}
+			}
+			Node::Leaf(leaf) => {
+				if !self.watchers.is_empty() {
+					deleted.extend(leaf.entries.into_iter().filter(|(k, _)| self.is_watched(k)));
+				}
+			}
+		}
+		self.free_page(num)
+	}
+
+	/// Rebalance the underflowed childs of the `branch`, Which may have lost any number of entries (or childs).
+	///
+	/// Unlike a single delete, The sibling may be underflowed too, So merging them doesn't always fix the underflow.
+	/// And a merged (or balanced) branch may carry underflowed childs, That couldn't be fixed before (when it had a single child).
+	/// The caller is responsible for writing the `branch`.
+	pub(crate) fn fix_childs(&mut self, branch: &mut Branch<K, A::Output, SIZE>) -> Result<()> {
+		let mut i = 0;
+		while i < branch.childs.len() && branch.childs.len() > 1 {
+			let underflow = match self.node(branch.childs[i])? {
+				Node::Branch(child) => child.is_underflow(),
+				Node::Leaf(child) => child.is_underflow(),
+			};
+			if !underflow {
+				i += 1;
+				continue;
+			}
+			let l = i.saturating_sub(1);
+			self.rebalance(branch, i)?;
+			for &child in &branch.childs[l..branch.childs.len().min(l + 2)] {
+				if let Node::Branch(mut child_branch) = self.node(child)? {
+					self.fix_childs(&mut child_branch)?;
+					self.write(child, child_branch.to_bytes())?;
+				}
+			}
+			i = l;
+		}
+		Ok(())
+	}
+}
+
+fn is_empty<K: Key>(range: &impl RangeBounds<K>) -> bool {
+	match (range.start_bound(), range.end_bound()) {
+		(Bound::Included(s), Bound::Included(e)) => s > e,
+		(Bound::Included(s), Bound::Excluded(e))
+		| (Bound::Excluded(s), Bound::Included(e))
+		| (Bound::Excluded(s), Bound::Excluded(e)) => s >= e,
+		_ => false,
+	}
+}
@@ -0,0 +1,58 @@
+use super::*;
+
+/// The current value of the entry, When a [`BPlusTree::compare_and_swap`] fails. `None` means absent.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Current<V>(pub Option<V>);
+
+/// Outcome of a conditional write.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Outcome<V> {
+	/// The entry was absent, And it is inserted.
+	Inserted,
+	/// The entry was present, And it is updated. Contains the old value.
+	Updated(V),
+	/// The entry is already present, Nothing is changed. Contains the current value.
+	Exists(V),
+	/// The entry is absent, Nothing is changed.
+	Missing,
+}
+
+impl<K: Key, V: Key, const SIZE: usize, A: Aggregate<K, V>> BPlusTree<K, V, SIZE, A> {
+	/// #### _Blocking_
+	///
+	/// Replace the entry with `new`, Only if its current value is `expected`. `None` means absent,
+	/// So `new: None` deletes the entry and `expected: None` inserts it.
+	///
+	/// Returns the current value, If it doesn't match `expected`.
+	pub fn compare_and_swap(
+		&mut self,
+		key: K,
+		expected: Option<V>,
+		new: Option<V>,
+	) -> Result<std::result::Result<(), Current<V>>> {
+		let (old, _) = self.modify(key, |old| if old == expected { new } else { old })?;
+		Ok(if old == expected { Ok(()) } else { Err(Current(old)) })
+	}
+
+	/// #### _Blocking_
+	///
+	/// Insert the entry, Only if it is absent.
+	/// Returns [`Outcome::Inserted`] or [`Outcome::Exists`].
+	pub fn insert_if_absent(&mut self, key: K, value: V) -> Result<Outcome<V>> {
+		Ok(match self.modify(key, |old| old.or(Some(value)))? {
+			(Some(old), _) => Outcome::Exists(old),
+			(None, _) => Outcome::Inserted,
+		})
+	}
+
+	/// #### _Blocking_
+	///
+	/// Update the entry, Only if it is present.
+	/// Returns [`Outcome::Updated`] or [`Outcome::Missing`].
+	pub fn update_if_present(&mut self, key: K, value: V) -> Result<Outcome<V>> {
+		Ok(match self.modify(key, |old| old.map(|_| value))? {
+			(Some(old), _) => Outcome::Updated(old),
+			(None, _) => Outcome::Missing,
+		})
+	}
+}
@@ -0,0 +1,143 @@
+use super::*;
+use std::fmt::Debug;
+use std::io::Write;
+use std::mem::replace;
+
+impl<K: Key, V: Key, const SIZE: usize, A: Aggregate<K, V>> BPlusTree<K, V, SIZE, A> {
+	/// #### _Blocking_
+	///
+	/// Render the tree in [Graphviz](https://graphviz.org) `dot` language.
+	///
+	/// Solid edges point from separator keys to childs,
+	/// Dashed edges are `next` links and dotted edges are `prev` links of leaves.
+	pub fn dump_dot(&self, mut w: impl Write) -> Result<()> {
+		writeln!(w, "digraph BPlusTree {{")?;
+		writeln!(w, "  node [shape=record];")?;
+		self.visit(self.root, &mut |num, node| {
+			match node {
+				Node::Branch(branch) => {
+					let mut label = format!("#{}", num);
+					for (i, key) in branch.keys.iter().enumerate() {
+						label += &format!("|<c{}>|{}", i, dot_escape(key));
+					}
+					label += &format!("|<c{}>", branch.keys.len());
+					writeln!(w, "  n{} [label=\"{}\"];", num, label)?;
+					for (i, child) in branch.childs.iter().enumerate() {
+						writeln!(w, "  n{}:c{} -> n{};", num, i, child)?;
+					}
+				}
+				Node::Leaf(leaf) => {
+					let mut label = format!("#{}", num);
+					for (k, v) in leaf.entries.iter() {
+						label += &format!("|{}: {}", dot_escape(k), dot_escape(v));
+					}
+					writeln!(w, "  n{} [label=\"{{{}}}\"];", num, label)?;
+					if leaf.next != 0 {
+						writeln!(w, "  n{} -> n{} [style=dashed, constraint=false];", num, leaf.next)?;
+					}
+					if leaf.prev != 0 {
+						writeln!(w, "  n{} -> n{} [style=dotted, constraint=false];", num, leaf.prev)?;
+					}
+				}
+			}
+			Ok(())
+		})?;
+		Ok(writeln!(w, "}}")?)
+	}
+
+	/// #### _Blocking_
+	///
+	/// Dump every node in pre-order as JSON. Keys, values and aggregates are `Debug` formatted strings.
+	///
+	/// Each child of a branch has its subtree count and aggregate, See [`BPlusTree::verify`].
+	///
+	/// ```json
+	/// { "root": 3, "len": 4, "nodes": [
+	///   { "page": 3, "type": "branch", "keys": ["3"], "childs": [1, 2], "counts": [2, 2], "aggs": ["()", "()"] },
+	///   { "page": 1, "type": "leaf", "prev": 0, "next": 2, "entries": [["1", "1"], ["2", "2"]] },
+	///   ...
+	/// ]}
+	/// ```
+	pub fn dump_json(&self, mut w: impl Write) -> Result<()> {
+		write!(w, "{{\"root\":{},\"len\":{},\"nodes\":[", self.root, self.len)?;
+		let mut first = true;
+		self.visit(self.root, &mut |num, node| {
+			if !replace(&mut first, false) {
+				write!(w, ",")?;
+			}
+			match node {
+				Node::Branch(branch) => {
+					let keys: Vec<_> = branch.keys.iter().map(json_string).collect();
+					let childs: Vec<_> = branch.childs.iter().map(u16::to_string).collect();
+					let counts: Vec<_> = branch.counts.iter().map(u32::to_string).collect();
+					let aggs: Vec<_> = branch.aggs.iter().map(json_string).collect();
+					write!(
+						w,
+						"{{\"page\":{},\"type\":\"branch\",\"keys\":[{}],\"childs\":[{}],\"counts\":[{}],\"aggs\":[{}]}}",
+						num,
+						keys.join(","),
+						childs.join(","),
+						counts.join(","),
+						aggs.join(",")
+					)
+				}
+				Node::Leaf(leaf) => {
+					let entries: Vec<_> = leaf
+						.entries
+						.iter()
+						.map(|(k, v)| format!("[{},{}]", json_string(k), json_string(v)))
+						.collect();
+					write!(
+						w,
+						"{{\"page\":{},\"type\":\"leaf\",\"prev\":{},\"next\":{},\"entries\":[{}]}}",
+						num,
+						leaf.prev,
+						leaf.next,
+						entries.join(",")
+					)
+				}
+			}?;
+			Ok(())
+		})?;
+		Ok(write!(w, "]}}")?)
+	}
+
+	/// Visit every node in pre-order.
+	fn visit(&self, num: u16, f: &mut impl FnMut(u16, Node<K, V, A::Output, SIZE>) -> Result<()>) -> Result<()> {
+		let node = self.node(num)?;
+		let childs = match &node {
+			Node::Branch(branch) => branch.childs.clone(),
+			Node::Leaf(_) => Vec::new(),
+		};
+		f(num, node)?;
+		for child in childs {
+			self.visit(child, f)?;
+		}
+		Ok(())
+	}
+}
+
+fn dot_escape(v: &impl Debug) -> String {
+	let mut out = String::new();
+	for ch in format!("{:?}", v).chars() {
+		if matches!(ch, '{' | '}' | '|' | '<' | '>' | '"' | '\\') {
+			out.push('\\');
+		}
+		out.push(ch);
+	}
+	out
+}
+
+fn json_string(v: &impl Debug) -> String {
+	let mut out = String::from('"');
+	for ch in format!("{:?}", v).chars() {
+		match ch {
+			'"' => out += "\\\"",
+			'\\' => out += "\\\\",
+			ch if ch.is_control() => out += &format!("\\u{:04x}", ch as u32),
+			ch => out.push(ch),
+		}
+	}
+	out.push('"');
+	out
+}
@@ -27,3 +27,39 @@ impl<const N: usize> Key for [u8; N] {
 		bytes.try_into().unwrap()
 	}
 }
+
+impl Key for () {
+	const SIZE: usize = 0;
+	fn to_bytes(self) -> Vec<u8> {
+		Vec::new()
+	}
+	fn from_bytes(_: &[u8]) -> Self {}
+}
+
+/// Tag (1) + `T::SIZE`
+impl<T: Key> Key for Option<T> {
+	const SIZE: usize = 1 + T::SIZE;
+	fn to_bytes(self) -> Vec<u8> {
+		match self {
+			None => vec![0; Self::SIZE],
+			Some(v) => [vec![1], v.to_bytes()].concat(),
+		}
+	}
+	fn from_bytes(bytes: &[u8]) -> Self {
+		match bytes[0] {
+			0 => None,
+			_ => Some(T::from_bytes(&bytes[1..])),
+		}
+	}
+}
+
+/// Compared in lexicographic order.
+impl<A: Key, B: Key> Key for (A, B) {
+	const SIZE: usize = A::SIZE + B::SIZE;
+	fn to_bytes(self) -> Vec<u8> {
+		[self.0.to_bytes(), self.1.to_bytes()].concat()
+	}
+	fn from_bytes(bytes: &[u8]) -> Self {
+		(A::from_bytes(&bytes[..A::SIZE]), B::from_bytes(&bytes[A::SIZE..]))
+	}
+}
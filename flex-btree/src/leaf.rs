@@ -1,7 +1,10 @@
+use std::cmp::Ordering;
+use std::marker::PhantomData;
 use std::mem::replace;
 
 use bytes::{Buf, BufMut};
 
+use crate::aggregate::Aggregate;
 use crate::entry::Key;
 use SetOption::*;
 
@@ -27,6 +30,10 @@ impl<K: Key, V: Key, const SIZE: usize> Leaf<K, V, SIZE> {
 		self.entries.len() > (Self::capacity() / 2)
 	}
 
+	pub fn is_underflow(&self) -> bool {
+		self.entries.len() < Self::capacity() / 2
+	}
+
 	pub fn new() -> Self {
 		Self {
 			next: 0,
@@ -54,13 +61,32 @@ impl<K: Key, V: Key, const SIZE: usize> Leaf<K, V, SIZE> {
 
 	/// This function splits `Self` at the middle and returns the right half.
 	pub fn split_at_mid(&mut self) -> (Self, K) {
+		self.split_at(self.entries.len() / 2)
+	}
+
+	/// Split `Self` so that it keeps `at` entries, And returns the other half with its first key.
+	pub fn split_at(&mut self, at: usize) -> (Self, K) {
 		let mut other = Self::new();
-		let mid_point = self.entries.len() / 2;
-		other.entries = self.entries.drain(mid_point..).collect();
+		other.entries = self.entries.drain(at..).collect();
 		let mid = other.entries[0].0;
 		(other, mid)
 	}
 
+	pub fn aggregate<A: Aggregate<K, V>>(&self) -> A::Output {
+		self.entries
+			.iter()
+			.fold(A::identity(), |acc, (k, v)| A::combine(acc, A::lift(k, v)))
+	}
+
+	/// Move entries between `self` and its right sibling, So both have almost same number of entries.
+	/// Returns the new separator key, Which is the first key of the right sibling.
+	pub fn balance(&mut self, right: &mut Self) -> K {
+		let mid = (self.entries.len() + right.entries.len()) / 2;
+		self.entries.append(&mut right.entries);
+		right.entries = self.entries.split_off(mid);
+		right.entries[0].0
+	}
+
 	pub fn to_bytes(&self) -> [u8; SIZE] {
 		let mut buf = [0; SIZE];
 		let mut view = buf.as_mut();
@@ -100,6 +126,69 @@ impl<K: Key, V: Key, const SIZE: usize> Leaf<K, V, SIZE> {
 	}
 }
 
+/// A leaf that is borrowed from the raw bytes of its page, Without decoding it.
+/// Only the keys that are compared (and the entries that are asked for) get decoded.
+pub struct LeafRef<'a, K, V, const SIZE: usize> {
+	bytes: &'a [u8; SIZE],
+	_marker: PhantomData<(K, V)>,
+}
+
+impl<'a, K: Key, V: Key, const SIZE: usize> LeafRef<'a, K, V, SIZE> {
+	// Node type (1) + next (2) + prev (2) + entries len (2)
+	const HEADER: usize = 7;
+
+	/// Returns `None` if the number of entries exceeds the capacity.
+	pub fn new(bytes: &'a [u8; SIZE]) -> Option<Self> {
+		let this = Self::new_unchecked(bytes);
+		(this.len() <= Leaf::<K, V, SIZE>::capacity()).then(|| this)
+	}
+
+	/// `bytes` must be a valid leaf, See [`LeafRef::new`].
+	pub fn new_unchecked(bytes: &'a [u8; SIZE]) -> Self {
+		Self { bytes, _marker: PhantomData }
+	}
+
+	pub fn next(&self) -> u16 {
+		u16::from_le_bytes([self.bytes[1], self.bytes[2]])
+	}
+
+	pub fn prev(&self) -> u16 {
+		u16::from_le_bytes([self.bytes[3], self.bytes[4]])
+	}
+
+	pub fn len(&self) -> usize {
+		u16::from_le_bytes([self.bytes[5], self.bytes[6]]) as usize
+	}
+
+	pub fn key(&self, index: usize) -> K {
+		let offset = Self::HEADER + index * (K::SIZE + V::SIZE);
+		K::from_bytes(&self.bytes[offset..offset + K::SIZE])
+	}
+
+	pub fn value(&self, index: usize) -> V {
+		let offset = Self::HEADER + index * (K::SIZE + V::SIZE) + K::SIZE;
+		V::from_bytes(&self.bytes[offset..offset + V::SIZE])
+	}
+
+	/// Same as [`Leaf::binary_search`], But the keys are decoded in place.
+	pub fn binary_search(&self, key: &K) -> Result<usize, usize> {
+		let (mut low, mut high) = (0, self.len());
+		while low < high {
+			let mid = low + (high - low) / 2;
+			match self.key(mid).partial_cmp(key).expect("Key can't be `NaN`") {
+				Ordering::Less => low = mid + 1,
+				Ordering::Greater => high = mid,
+				Ordering::Equal => return Ok(mid),
+			}
+		}
+		Err(low)
+	}
+
+	pub fn get(&self, key: &K) -> Option<V> {
+		self.binary_search(key).ok().map(|index| self.value(index))
+	}
+}
+
 #[cfg(test)]
 mod tests {
 	use super::*;
@@ -146,6 +235,23 @@ mod tests {
 		assert_eq!(leaf.entries[..], leaf2.entries[..]);
 	}
 
+	#[test]
+	fn leaf_ref() {
+		let mut leaf = Leaf::new();
+		leaf.next = 1;
+		leaf.prev = 2;
+		leaf.entries = (0..100).map(|i| (i * 2, i as u16)).collect();
+
+		let bytes = leaf.to_bytes();
+		let leaf_ref = LeafRef::<u64, u16, 4096>::new(&bytes).unwrap();
+		assert_eq!((leaf_ref.next(), leaf_ref.prev(), leaf_ref.len()), (1, 2, 100));
+		for key in 0..200 {
+			assert_eq!(leaf_ref.binary_search(&key), leaf.binary_search(&key));
+		}
+		assert_eq!(leaf_ref.get(&42), Some(21));
+		assert_eq!(leaf_ref.get(&43), None);
+	}
+
 	#[test]
 	fn split_at_mid() {
 		let mut left = Leaf::new();
@@ -156,4 +262,23 @@ mod tests {
 		assert_eq!(right.entries.len(), 3);
 		assert_eq!(mid, 3);
 	}
+
+	#[test]
+	fn balance() {
+		let mut left = Leaf::new();
+		let mut right = Leaf::new();
+		left.entries = [(1, 1)].to_vec();
+		right.entries = [(2, 2), (3, 3), (4, 4), (5, 5)].to_vec();
+
+		assert_eq!(left.balance(&mut right), 3);
+		assert_eq!(left.entries, [(1, 1), (2, 2)]);
+		assert_eq!(right.entries, [(3, 3), (4, 4), (5, 5)]);
+
+		left.entries = [(1, 1), (2, 2), (3, 3), (4, 4)].to_vec();
+		right.entries = [(5, 5)].to_vec();
+
+		assert_eq!(left.balance(&mut right), 3);
+		assert_eq!(left.entries, [(1, 1), (2, 2)]);
+		assert_eq!(right.entries, [(3, 3), (4, 4), (5, 5)]);
+	}
 }
\ No newline at end of file
@@ -1,27 +1,55 @@
 #![allow(warnings)]
 
+mod aggregate;
+mod any;
+mod append;
+mod batch;
 mod branch;
+mod bulk;
+mod cas;
+mod dump;
 mod entry;
 mod leaf;
+mod merge;
 mod meta;
 mod node;
+mod pin;
+mod pop;
+mod rank;
+mod stats;
+mod ttl;
+mod verify;
 mod view;
+mod watch;
 
 use flex_page::Pages;
 
 use meta::{MetaInfo, Metadata};
+use std::convert::TryFrom;
 use std::fs::File;
-use std::io::{Error, ErrorKind, Result};
 use std::marker::PhantomData;
 use std::path::Path;
 
-use branch::{Branch, DeleteOperation};
-use entry::Key;
-use leaf::Leaf;
-use node::Node;
+use branch::{Branch, BranchRef};
+use leaf::{Leaf, LeafRef};
+use merge::MergeOperator;
+use node::{Node, NodeRef};
+use pin::Pinned;
+use watch::Watcher;
 
+pub use aggregate::{Aggregate, Count, Max, Min, Sum, Summable};
+pub use flex::{Error, Result};
+pub use any::{convert_page_size, page_size, AnyTree, PAGE_SIZES};
+pub use cas::{Current, Outcome};
+pub use entry::Key;
+pub use flex_derive::BTreeKey as Key;
 pub use leaf::SetOption;
+pub use pin::DEFAULT_PINNED_LEVELS;
+pub use stats::{Level, Stats};
+pub use ttl::{TtlTree, TtlView};
+pub use verify::{verify, Problem, Report};
 pub use view::View;
+pub use watch::Event;
 
 pub enum Get<K> {
 	First,
@@ -29,16 +57,37 @@ pub enum Get<K> {
 	Exact(K),
 }
 
-pub struct BPlusTree<K, V, const SIZE: usize> {
+/// Default [`BPlusTree::fill_factor`], In percent.
+pub const DEFAULT_FILL_FACTOR: u8 = 90;
+
+/// `A` is an optional aggregate of the entries, That is maintained for each subtree. See [`Aggregate`].
+pub struct BPlusTree<K, V, const SIZE: usize, A = ()> {
 	len: u32,
 	root: u16,
+	/// Head of the free page list.
+	free: u16,
+	/// See [`BPlusTree::set_fill_factor`].
+	fill_factor: u8,
 	pages: Pages<SIZE>,
-	_marker: PhantomData<(K, V)>,
+	/// See [`BPlusTree::pin_levels`].
+	pinned: Pinned<SIZE>,
+	watchers: Vec<Watcher<K, V>>,
+	merge_operator: Option<MergeOperator<V>>,
+	/// See [`BPlusTree::page_writes`].
+	writes: u64,
+	_marker: PhantomData<(K, V, A)>,
 }
 
-impl<K: Key, V: Key, const SIZE: usize> BPlusTree<K, V, SIZE> {
+impl<K: Key, V: Key, const SIZE: usize, A: Aggregate<K, V>> BPlusTree<K, V, SIZE, A> {
 	/// #### _Blocking_
 	pub fn open(path: impl AsRef<Path>) -> Result<Self> {
+		// Sizes are stored in a single byte, And each node must have room for at least 2 entries (or childs).
+		if [K::SIZE, V::SIZE, <A::Output as Key>::SIZE].iter().any(|&size| size > u8::MAX as usize)
+			|| Leaf::<K, V, SIZE>::capacity() < 2
+			|| Branch::<K, A::Output, SIZE>::capacity() < 2
+		{
+			return Err(Error::KeyTooLarge);
+		}
 		let file = File::options()
 			.read(true)
 			.write(true)
@@ -46,55 +95,127 @@ impl<K: Key, V: Key, const SIZE: usize> BPlusTree<K, V, SIZE> {
 			.open(path)?;
 
 		let pages = Pages::open(file)?;
-		let metainfo = MetaInfo::new::<K, V, SIZE>();
+		let metainfo = MetaInfo::new::<K, V, A::Output, SIZE>();
 
 		let mut len = 0;
 		let mut root = 1;
+		let mut free = 0;
+		let mut fill_factor = DEFAULT_FILL_FACTOR;
 		let mut raw_meta = [0; SIZE];
 
 		if pages.len() == 0 {
 			pages.alloc(2)?; // 1 for metadata, 1 for root node
-			raw_meta[..6].copy_from_slice(&metainfo.to_bytes());
+			raw_meta[..MetaInfo::SIZE].copy_from_slice(&metainfo.to_bytes());
 		} else {
 			raw_meta = pages.read(0)?;
-			let info = MetaInfo::from_bytes(&raw_meta[..6]);
-			if info != metainfo {
-				return Err(Error::new(
-					ErrorKind::InvalidInput,
-					format!("Expected: {:?}, but got: {:?}", info, metainfo),
-				));
-			}
-			let metadata = Metadata::from_bytes(&raw_meta[6..]);
+			MetaInfo::ensure::<K, V, A::Output, SIZE>(&raw_meta[..MetaInfo::SIZE])?;
+			let metadata = Metadata::from_bytes(&raw_meta[MetaInfo::SIZE..]);
 			if metadata.is_opened == 1 {
-				return Err(Error::new(
-					ErrorKind::AddrInUse,
-					"The file is already opened",
-				));
+				return Err(Error::AlreadyOpen);
 			}
 			len = metadata.len;
 			root = metadata.root;
+			free = metadata.free;
+			if metadata.fill_factor != 0 {
+				fill_factor = metadata.fill_factor;
+			}
 		};
 		// `metadata.is_opened` flag.
-		raw_meta[6] = 1;
+		raw_meta[MetaInfo::SIZE] = 1;
 		pages.write(0, raw_meta)?;
-		Ok(Self {
+		let mut tree = Self {
 			len,
 			root,
+			free,
+			fill_factor,
 			pages,
+			pinned: Pinned::new(),
+			watchers: Vec::new(),
+			merge_operator: None,
+			writes: 0,
 			_marker: PhantomData,
-		})
+		};
+		tree.settle()?;
+		Ok(tree)
 	}
 
 	pub fn len(&self) -> u32 {
 		self.len
 	}
 
+	pub fn page_size(&self) -> usize {
+		SIZE
+	}
+
+	/// Number of pages written since the tree is opened.
+	pub fn page_writes(&self) -> u64 {
+		self.writes
+	}
+
+	/// Target fill of the nodes, In percent. See [`BPlusTree::set_fill_factor`].
+	pub fn fill_factor(&self) -> u8 {
+		self.fill_factor
+	}
+
+	/// Set the target fill of the nodes (in percent, `50..=100`), It is stored in the metadata.
+	///
+	/// It is used in only two cases:
+	/// - When a node on the right most path of the tree is splitted by an append (the new key is the largest one),
+	///   By [`BPlusTree::set`] or [`BPlusTree::merge`]. The left node keeps `fill_factor` percent of the entries,
+	///   So time-ordered keys don't leave every node half empty.
+	/// - Bulk operations (like [`BPlusTree::append`]) fill the nodes up to it.
+	///
+	/// Every other split is at the middle, Whatever the fill factor is.
+	pub fn set_fill_factor(&mut self, percent: u8) -> Result<()> {
+		if !(50..=100).contains(&percent) {
+			return Err(Error::InvalidInput("Fill factor must be within 50..=100".into()));
+		}
+		self.fill_factor = percent;
+		Ok(())
+	}
+
+	/// Number of entries (or childs) to keep, When a node with `len` entries is filled up to the fill factor.
+	pub(crate) fn fill(&self, len: usize) -> usize {
+		(len * self.fill_factor as usize / 100).clamp(1, len - 1)
+	}
+
+	/// Same as [`Self::fill`] for the childs of a branch, But the right sibling keeps two childs at least.
+	/// So an underflowed child of it can still be merged with (or borrow from) its sibling.
+	pub(crate) fn fill_branch(&self, len: usize) -> usize {
+		self.fill(len).min(len - 2).max(1)
+	}
+
 	/// #### _Blocking_
 	pub fn clear(&mut self) -> Result<()> {
+		let mut deleted = Vec::new();
+		if !self.watchers.is_empty() {
+			let mut view = self.get(Get::First)?;
+			loop {
+				deleted.extend(view.iter().filter(|(k, _)| self.is_watched(k)));
+				if !view.next()? {
+					break;
+				}
+			}
+		}
 		self.len = 0;
 		self.root = 1;
-		self.pages.write(self.root as u64, [0; SIZE])?;
-		self.pages.set_len(2)
+		self.free = 0;
+		self.write(self.root, [0; SIZE])?;
+		self.pages.set_len(2)?;
+		self.unpin();
+		self.settle()?;
+		for (key, old) in deleted {
+			self.notify(Event::Deleted { key, old });
+		}
+		Ok(())
+	}
+
+	/// #### _Blocking_
+	///
+	/// Walk every page from the root and report structural problems.
+	/// See [`verify()`] for files that are not opened.
+	pub fn verify(&self) -> Result<Report<K>> {
+		verify::check::<K, V, A, SIZE>(&self.pages, self.root, self.free, self.len)
 	}
 
 	/// #### _Blocking_
@@ -104,242 +225,339 @@ impl<K: Key, V: Key, const SIZE: usize> BPlusTree<K, V, SIZE> {
 
 	/// #### _Blocking_
 	pub fn get(&self, opt: Get<K>) -> Result<View<K, V, SIZE>> {
+		if let Get::Exact(key) = &opt {
+			ensure_ord(key)?;
+		}
 		let mut page_no = self.root;
-		let leaf = loop {
-			page_no = match Node::from_bytes(self.pages.read(page_no as u64)?) {
-				Node::Branch(b) => match opt {
-					Get::First => b.childs[0],
-					Get::Last => *b.childs.last().unwrap(),
-					Get::Exact(key) => b.child_at(b.lookup(&key)),
+		loop {
+			let bytes = self.read(page_no)?;
+			page_no = match self.node_ref(page_no, &bytes)? {
+				NodeRef::Branch(b) => match opt {
+					Get::First => b.child(0),
+					Get::Last => b.child(b.len() - 1),
+					Get::Exact(key) => b.child(b.lookup(&key)),
 				},
-				Node::Leaf(leaf) => break leaf,
+				NodeRef::Leaf(_) => return Ok(View::new(&self.pages, bytes)),
 			}
-		};
-		Ok(View {
-			leaf,
-			pages: &self.pages,
-		})
+		}
 	}
 
+	/// #### _Blocking_
 	pub fn delete(&mut self, key: &K) -> Result<Option<(K, V)>> {
-		let bytes = self.pages.read(self.root as u64)?;
-		let (page_no, is_key_present_in_idx, sibings) = match Node::from_bytes(bytes) {
-			Node::Branch(branch) => {
-				let lookup_idx = branch.lookup(key);
-				(
-					branch.child_at(lookup_idx),
-					branch.keys.contains(&key),
-					branch.sibings_at(lookup_idx),
-				)
-			}
-			Node::Leaf(mut leaf) => {
-				return match leaf.binary_search(&key) {
-					Ok(index) => Ok(Some(leaf.entries.remove(index))),
-					Err(_) => Ok(None),
-				}
-			}
-		};
-		todo!()
-		// let result = self._delete(key, self.root, false)?;
-		// if result.is_some() {
-		// 	self.len -= 1;
-		// }
-		// Ok(result)
+		ensure_ord(key)?;
+		let (ret, _, _) = self._delete(self.root, key)?;
+		if let Some((key, old)) = ret {
+			self.len -= 1;
+			self.shrink()?;
+			self.notify(Event::Deleted { key, old });
+		}
+		Ok(ret)
 	}
 
 	/// ### Delete Operation
 	///
-	/// The complexity of the delete procedure in the B+ Tree surpasses that of the insert and search functionality.
-	/// Before going through the steps below, one must know these facts about a B+ tree of degree m.
-	//
-	/// 1. A node can have a maximum of m children. (i.e. 3)
-	/// 2. A node can contain a maximum of m - 1 keys. (i.e. 2)
-	/// 3. A node should have a minimum of ⌈m/2⌉ children. (i.e. 2)
-	/// 4. A node (except root node) should contain a minimum of ⌈m/2⌉ - 1 keys. (i.e. 1)
+	/// Remove the key from the leaf, Then fix the underflowed nodes on the way back to the root.
+	/// A node (except root node) underflows when it has less than half of its capacity.
 	///
-	/// ### Case 1:
+	/// - If the underflowed node and its immediate sibling fit in a single node, Merge them,
+	///   And remove their separator key from the parent. The page of the right node is freed.
 	///
-	/// The key to be deleted is present only at the leaf node not in the indexes (or internal nodes).
-	/// There are two cases for it:
+	/// - Otherwise borrow entries from the sibling (through the parent), So both have almost
+	///   same number of entries. And replace their separator key in the parent.
 	///
-	/// - There is more than the minimum number of keys in the node. Simply delete the key.
-	///   > Deleting `40` <img src="https://cdn.programiz.com/sites/tutorial2program/files/deletion-1-b+tree.png" width=400/>
+	/// Separator keys don't need to be updated when the first key of a leaf is deleted,
+	/// Because a separator key is only a lower bound of its right subtree.
 	///
-	/// - There is an exact minimum number of keys in the node. Delete the key and borrow a key from the immediate sibling. Add the median key of the sibling node to the parent.
-	///   > Deleting `5` <img src="https://cdn.programiz.com/sites/tutorial2program/files/deletion-2-b+tree.png" width=400/>
-	///
-	/// ### Case 2:
-	///
-	/// The key to be deleted is present in the internal nodes as well. Then we have to remove them from the internal nodes as well. There are the following cases for this situation.
-	///
-	/// - If there is more than the minimum number of keys in the node, simply delete the key from the leaf node and delete the key from the internal node as well.
-	///   Fill the empty space in the internal node with the inorder successor.
-	///   > Deleting `45` <img src="https://cdn.programiz.com/sites/tutorial2program/files/deletion-3-b+tree_0.png" width=350/>
-	///
-	/// - If there is an exact minimum number of keys in the node, then delete the key and borrow a key from its immediate sibling (through the parent).
-	///   Fill the empty space created in the index (internal node) with the borrowed key.
-	///   > Deleting `35` <img src="https://cdn.programiz.com/sites/tutorial2program/files/deletion-4-b+tree.png" width=350/>
-	///
-	/// - This case is similar to Case II(1) but here, empty space is generated above the immediate parent node.
-	///   After deleting the key, merge the empty space with its sibling.
-	///   Fill the empty space in the grandparent node with the inorder successor.
-	///   > Deleting `25` <img src="https://cdn.programiz.com/sites/tutorial2program/files/deletion-5-b+tree.png" width=350/>
-	///
-	/// ### Case 3:
-	///
-	/// In this case, the height of the tree gets shrinked. It is a little complicated.Deleting 55 from the tree below leads to this condition. It can be understood in the illustrations below.
-	/// > Deleting `55` <img src="https://cdn.programiz.com/sites/tutorial2program/files/deletion-6-b+tree.png" width=350/>
-	fn _delete(
-		&self,
-		key: K,
-		page_no: u16,
-		key_is_idx: bool,
-		(left_sibling, right_sibling): (Option<u16>, Option<u16>),
-	) -> Result<(Option<(K, V)>, DeleteOperation<K>)> {
-		use DeleteOperation::*;
-		let mut deleted_entry = None;
-		let mut operation = Nothing;
-
-		match Node::from_bytes(self.pages.read(page_no as u64)?) {
+	/// Returns the deleted entry, The new aggregate of the node and whether the node is underflowed.
+	fn _delete(&mut self, num: u16, key: &K) -> Result<(Option<(K, V)>, A::Output, bool)> {
+		match self.node(num)? {
+			Node::Leaf(mut leaf) => {
+				let entry = match leaf.binary_search(key) {
+					Ok(index) => leaf.entries.remove(index),
+					Err(_) => return Ok((None, A::identity(), false)),
+				};
+				self.write(num, leaf.to_bytes())?;
+				Ok((Some(entry), leaf.aggregate::<A>(), leaf.is_underflow()))
+			}
 			Node::Branch(mut branch) => {
-				let lookup_idx = branch.lookup(&key);
-				let res = self._delete(
-					key,
-					branch.child_at(lookup_idx),
-					key_is_idx || branch.binary_search(&key).is_ok(),
-					branch.sibings_at(lookup_idx),
-				)?;
-				deleted_entry = res.0;
-
-				match res.1 {
-					UpdateIdx(k) => {
-						if key_is_idx {
-							operation = UpdateIdx(key);
-						} else {
-							*branch.get_key_at(lookup_idx) = k;
-						}
-					}
-					ReplaceIdx(k) => {
-						let key = branch.get_key_at(lookup_idx);
-						*key = k;
-						if key_is_idx {
-							operation = UpdateIdx(*key);
-						}
-					}
-					RemoveIdx => {
-						let _free_key = branch.remove_key_at(lookup_idx);
-						let _free_page = branch.childs.remove(lookup_idx);
-					}
-					_ => {}
+				let index = branch.lookup(key);
+				let (ret, agg, underflow) = self._delete(branch.child_at(index), key)?;
+				if ret.is_none() {
+					return Ok((None, agg, false));
+				}
+				branch.counts[index] -= 1;
+				branch.aggs[index] = agg;
+				if underflow {
+					self.rebalance(&mut branch, index)?;
 				}
+				self.write(num, branch.to_bytes())?;
+				Ok((ret, branch.aggregate::<V, A>(), branch.is_underflow()))
 			}
-			Node::Leaf(mut leaf) => {
-				if let Ok(idx) = leaf.binary_search(&key) {
-					let should_steal_key_from_sibling = !leaf.is_half_full();
-					deleted_entry = Some(leaf.entries.remove(idx));
-
-					if should_steal_key_from_sibling {
-						operation = loop {
-							if let Some(link) = left_sibling {
-								let mut sibling = Leaf::from_bytes(self.pages.read(link as u64)?);
-								if sibling.is_half_full() {
-									leaf.entries.push(sibling.entries.pop().unwrap());
-									break ReplaceIdx(leaf.entries.last().unwrap().0);
-								} else {
-									// Margeing the sibling with the leaf.
-									sibling.entries.extend(leaf.entries.drain(..));
-								}
-							}
-							if let Some(link) = right_sibling {
-								let mut sibling = Leaf::from_bytes(self.pages.read(link as u64)?);
-								if sibling.is_half_full() {
-									leaf.entries.insert(0, sibling.entries.remove(0));
-									break ReplaceIdx(leaf.entries.first().unwrap().0);
-								} else {
-									sibling.entries.extend(leaf.entries.drain(..));
-								}
-							}
-							break RemoveIdx;
-						};
-					} else if key_is_idx {
-						operation = UpdateIdx(leaf.entries.first().unwrap().0);
+		}
+	}
+
+	/// Fix the underflowed child at `index`, By merging it with its immediate sibling or borrowing from it.
+	/// The caller is responsible for writing the `branch`.
+	fn rebalance(&mut self, branch: &mut Branch<K, A::Output, SIZE>, index: usize) -> Result<()> {
+		if branch.childs.len() < 2 {
+			return Ok(());
+		}
+		// Always work with a pair of adjacent childs.
+		let l = if index == 0 { 0 } else { index - 1 };
+		let r = l + 1;
+		let (left_num, right_num) = (branch.childs[l], branch.childs[r]);
+
+		let left = self.node(left_num)?;
+		let right = self.node(right_num)?;
+		let left = match (left, right) {
+			(Node::Leaf(mut left), Node::Leaf(mut right)) => {
+				if left.entries.len() + right.entries.len() < Leaf::<K, V, SIZE>::capacity() {
+					left.entries.append(&mut right.entries);
+					left.next = right.next;
+					if right.next != 0 {
+						let mut next = Leaf::<K, V, SIZE>::from_bytes(self.read(right.next)?);
+						next.prev = left_num;
+						self.write(right.next, next.to_bytes())?;
 					}
+					branch.remove_child(r);
+					self.free_page(right_num)?;
+				} else {
+					branch.keys[l] = left.balance(&mut right);
+					branch.counts[l] = left.entries.len() as u32;
+					branch.counts[r] = right.entries.len() as u32;
+					branch.aggs[r] = right.aggregate::<A>();
+					self.write(right_num, right.to_bytes())?;
+				}
+				branch.aggs[l] = left.aggregate::<A>();
+				left.to_bytes()
+			}
+			(Node::Branch(mut left), Node::Branch(mut right)) => {
+				if left.childs.len() + right.childs.len() < Branch::<K, A::Output, SIZE>::capacity() {
+					left.merge(branch.keys[l], right);
+					branch.remove_child(r);
+					self.free_page(right_num)?;
+				} else {
+					branch.keys[l] = left.balance(branch.keys[l], &mut right);
+					branch.counts[l] = left.count();
+					branch.counts[r] = right.count();
+					branch.aggs[r] = right.aggregate::<V, A>();
+					self.write(right_num, right.to_bytes())?;
 				}
+				branch.aggs[l] = left.aggregate::<V, A>();
+				left.to_bytes()
+			}
+			_ => return Err(Error::Corrupted { page: right_num as u64 }),
+		};
+		self.write(left_num, left)
+	}
+
+	/// If the root branch is left with a single child, That child becomes the new root.
+	/// So the height of the tree gets shrinked.
+	fn shrink(&mut self) -> Result<()> {
+		while let Node::Branch(branch) = self.node(self.root)? {
+			if branch.childs.len() > 1 {
+				break;
 			}
+			self.free_page(self.root)?;
+			self.root = branch.childs[0];
+			self.unpin();
 		}
-		Ok((deleted_entry, operation))
+		self.settle()
 	}
 
 	/// #### _Blocking_
 	pub fn set(&mut self, key: K, value: V, opt: SetOption) -> Result<Option<V>> {
-		let (ret, marge) = self._set(self.root, key, value, opt)?;
-		if let Some((mid, right)) = marge {
-			let root_branch = Branch::create_root(mid, self.root, right);
-			// TODO: reuse free pages
-			self.root = self.pages.create(root_branch.to_bytes())? as u16;
-		};
+		ensure_ord(&key)?;
+		let update = matches!(opt, SetOption::UpdateOrInsert);
+		let (ret, agg, marge) = self._set(self.root, key, value, opt, true)?;
 		if ret.is_none() {
 			self.len += 1;
 		}
+		if let (Some(agg), Some(marge)) = (agg, marge) {
+			self.grow(agg, marge)?;
+		};
+		self.settle()?;
+		match ret {
+			None => self.notify(Event::Inserted { key, new: value }),
+			Some(old) if update => self.notify(Event::Updated { key, old, new: value }),
+			Some(_) => {}
+		}
 		Ok(ret)
 	}
 
+	/// Returns the old value, The new aggregate of the node (`None` if nothing is changed),
+	/// And if the node is splitted: the separator key, page number, number of entries and aggregate of the new right node.
+	///
+	/// `rightmost` is whether the node is on the right most path of the tree.
 	fn _set(
-		&self,
+		&mut self,
 		num: u16,
 		key: K,
 		value: V,
 		opt: SetOption,
-	) -> Result<(Option<V>, Option<(K, u16)>)> {
+		rightmost: bool,
+	) -> Result<(Option<V>, Option<A::Output>, Option<(K, u16, u32, A::Output)>)> {
 		let val;
 		let mut marge = None;
 
-		match Node::from_bytes(self.pages.read(num as u64)?) {
-			Node::Branch(mut branch) => {
-				let index = branch.lookup(&key);
-				let ret = self._set(branch.child_at(index), key, value, opt)?;
-				val = ret.0;
-				if let Some(e) = ret.1 {
-					branch.insert(index, e);
+		// Nodes are searched in place, And decoded only if they have to be written.
+		let bytes = self.read(num)?;
+		let agg = match self.node_ref(num, &bytes)? {
+			NodeRef::Branch(branch_ref) => {
+				let index = branch_ref.lookup(&key);
+				let last = rightmost && index == branch_ref.len() - 1;
+				let (ret, agg, split) = self._set(branch_ref.child(index), key, value, opt, last)?;
+				val = ret;
+				let agg = match agg {
+					Some(agg) => agg,
+					None => return Ok((val, None, None)),
+				};
+				// Nothing is changed in this subtree.
+				if val.is_some() && split.is_none() && branch_ref.agg(index) == agg {
+					return Ok((val, None, None));
+				}
+				let mut branch = Branch::from_bytes(bytes);
+				if val.is_none() {
+					branch.counts[index] += 1;
+				}
+				branch.aggs[index] = agg;
+				if let Some((mid, right, count, right_agg)) = split {
+					branch.counts[index] -= count;
+					branch.insert(index, (mid, right, count, right_agg));
 					if branch.is_full() {
-						let (other, mid) = branch.split_at_mid();
-						// TODO: reuse free pages
-						marge = Some((mid, self.pages.create(other.to_bytes())? as u16));
+						let len = branch.childs.len();
+						let at = if last { self.fill_branch(len) } else { len / 2 };
+						marge = Some(self.split_branch(&mut branch, at)?);
 					}
-					self.pages.write(num as u64, branch.to_bytes())?;
 				}
+				self.write(num, branch.to_bytes())?;
+				branch.aggregate::<V, A>()
 			}
-			Node::Leaf(mut leaf) => {
-				val = leaf.insert(key, value, opt.clone());
+			NodeRef::Leaf(leaf_ref) => {
 				// If `FindOrInsert` option is enable, And if the key is founded, Return early.
-				// So we don't need to write the page.
-				if matches!(opt, SetOption::FindOrInsert) && val.is_some() {
-					return Ok((val, None));
+				// So we don't need to decode (or write) the page.
+				if let (SetOption::FindOrInsert, Some(old)) = (&opt, leaf_ref.get(&key)) {
+					return Ok((Some(old), None, None));
 				}
+				let mut leaf = Leaf::from_bytes(bytes);
+				val = leaf.insert(key, value, opt);
 				// If the leaf is full, split it.
+				// An append to the right most leaf is splitted at the fill factor, Other splits are at the middle.
 				if leaf.is_full() {
-					let (mut right, mid) = leaf.split_at_mid();
-					right.prev = num;
-					// TODO: reuse free pages
-					leaf.next = self.pages.create(right.to_bytes())? as u16;
-					marge = Some((mid, leaf.next));
+					let len = leaf.entries.len();
+					let append = rightmost && leaf.entries[len - 1].0 == key;
+					let at = if append { self.fill(len) } else { len / 2 };
+					marge = Some(self.split_leaf(num, &mut leaf, at)?);
 				}
-				self.pages.write(num as u64, leaf.to_bytes())?;
+				self.write(num, leaf.to_bytes())?;
+				leaf.aggregate::<A>()
 			}
+		};
+		Ok((val, Some(agg), marge))
+	}
+
+	/// Create a new root above the splitted root.
+	fn grow(&mut self, agg: A::Output, (mid, right, count, right_agg): (K, u16, u32, A::Output)) -> Result<()> {
+		let root_branch = Branch::create_root(
+			mid,
+			(self.root, self.len - count, agg),
+			(right, count, right_agg),
+		);
+		self.root = self.create_page(root_branch.to_bytes())?;
+		self.unpin();
+		Ok(())
+	}
+
+	/// Move the entries of the leaf `num` after the first `at` entries to a new page.
+	/// Returns the separator key, page number, number of entries and aggregate of the new right leaf.
+	fn split_leaf(&mut self, num: u16, leaf: &mut Leaf<K, V, SIZE>, at: usize) -> Result<(K, u16, u32, A::Output)> {
+		let (mut right, mid) = leaf.split_at(at);
+		right.prev = num;
+		right.next = leaf.next;
+		let right_num = self.create_page(right.to_bytes())?;
+		if leaf.next != 0 {
+			let mut next = Leaf::<K, V, SIZE>::from_bytes(self.read(leaf.next)?);
+			next.prev = right_num;
+			self.write(leaf.next, next.to_bytes())?;
 		}
-		Ok((val, marge))
+		leaf.next = right_num;
+		Ok((mid, right_num, right.entries.len() as u32, right.aggregate::<A>()))
+	}
+
+	/// Same as [`Self::split_leaf`], But for branches.
+	fn split_branch(&mut self, branch: &mut Branch<K, A::Output, SIZE>, at: usize) -> Result<(K, u16, u32, A::Output)> {
+		let (other, mid) = branch.split_at(at);
+		let count = other.count();
+		let right_agg = other.aggregate::<V, A>();
+		Ok((mid, self.create_page(other.to_bytes())?, count, right_agg))
+	}
+
+	/// Reuse a page from the free list, Or allocate a new one.
+	fn create_page(&mut self, bytes: [u8; SIZE]) -> Result<u16> {
+		let num = self.alloc_page()?;
+		self.write(num, bytes)?;
+		Ok(num)
+	}
+
+	/// Same as [`Self::create_page`], But the page isn't written yet.
+	///
+	/// Returns [`Error::InvalidInput`] if the page number doesn't fit in `u16`.
+	fn alloc_page(&mut self) -> Result<u16> {
+		if self.free == 0 {
+			let num = self.pages.alloc(1)?;
+			return u16::try_from(num)
+				.map_err(|_| Error::InvalidInput(format!("The page number {} doesn't fit in `u16`.", num)));
+		}
+		let num = self.free;
+		let page = self.read(num)?;
+		self.free = u16::from_le_bytes([page[1], page[2]]);
+		Ok(num)
+	}
+
+	/// Push the page to the free list.
+	///
+	/// Free page layout: Node type (1) + next free page (2)
+	fn free_page(&mut self, num: u16) -> Result<()> {
+		let mut page = [0; SIZE];
+		page[0] = 2;
+		page[1..3].copy_from_slice(&self.free.to_le_bytes());
+		self.write(num, page)?;
+		self.free = num;
+		Ok(())
 	}
 }
 
-impl<K, V, const SIZE: usize> Drop for BPlusTree<K, V, SIZE> {
+/// Keys must be comparable with themselves, So `NaN` is rejected before it reaches a node.
+fn ensure_ord<K: Key>(key: &K) -> Result<()> {
+	match key.partial_cmp(key) {
+		Some(_) => Ok(()),
+		None => Err(Error::InvalidKey),
+	}
+}
+
+/// Same as [`ensure_ord`] for both bounds of the `range`.
+fn ensure_bounds<K: Key>(range: &impl std::ops::RangeBounds<K>) -> Result<()> {
+	use std::ops::Bound::*;
+	for bound in [range.start_bound(), range.end_bound()].iter() {
+		if let Included(key) | Excluded(key) = bound {
+			ensure_ord(*key)?;
+		}
+	}
+	Ok(())
+}
+
+impl<K, V, const SIZE: usize, A> Drop for BPlusTree<K, V, SIZE, A> {
 	fn drop(&mut self) {
 		let mut meta = self.pages.read(0).unwrap();
 		let metadata = Metadata {
 			is_opened: 0,
 			len: self.len,
 			root: self.root,
+			free: self.free,
+			fill_factor: self.fill_factor,
 		};
-		meta[6..13].copy_from_slice(&metadata.to_bytes());
+		let bytes = metadata.to_bytes();
+		meta[MetaInfo::SIZE..MetaInfo::SIZE + bytes.len()].copy_from_slice(&bytes);
 		self.pages.write(0, meta).unwrap();
 	}
 }
@@ -347,33 +565,6 @@ impl<K, V, const SIZE: usize> Drop for BPlusTree<K, V, SIZE> {
 #[cfg(test)]
 mod debug_tree {
 	use super::*;
-	#[derive(Debug)]
-	#[allow(dead_code)]
-	enum Tree<K, V> {
-		Branch {
-			keys: Vec<K>,
-			childs: Vec<Tree<K, V>>,
-		},
-		Leaf(Vec<(K, V)>),
-	}
-	impl<K: Key, V: Key, const SIZE: usize> BPlusTree<K, V, SIZE> {
-		fn build_tree(&self, num: u16) -> Tree<K, V> {
-			match Node::from_bytes(self.pages.read(num as u64).unwrap()) {
-				Node::Leaf(leaf) => Tree::Leaf(leaf.entries),
-				Node::Branch(branch) => Tree::Branch {
-					keys: branch.keys,
-					childs: branch
-						.childs
-						.iter()
-						.map(|&n| self.build_tree(n.into()))
-						.collect(),
-				},
-			}
-		}
-		fn debug_tree(&self) -> Tree<K, V> {
-			self.build_tree(self.root)
-		}
-	}
 
 	#[test]
 	#[ignore = "Only for debugging purpose"]
@@ -382,8 +573,8 @@ mod debug_tree {
 		for i in 1..=100 {
 			tree.set(i, i, SetOption::UpdateOrInsert).unwrap();
 		}
-		std::fs::write("tree.txt", format!("{:#?}", tree.debug_tree()))?;
-		std::fs::remove_file("debug_tree")
+		tree.dump_dot(File::create("tree.dot")?)?;
+		Ok(std::fs::remove_file("debug_tree")?)
 	}
 }
 
@@ -0,0 +1,128 @@
+use super::*;
+
+/// `f(old, operand) -> new`, Returning `None` deletes the entry.
+pub(crate) type MergeOperator<V> = Box<dyn Fn(Option<V>, V) -> Option<V> + Send + Sync>;
+
+impl<K: Key, V: Key, const SIZE: usize, A: Aggregate<K, V>> BPlusTree<K, V, SIZE, A> {
+	/// Register the function used by [`BPlusTree::merge`].
+	///
+	/// It isn't stored in the file, So it has to be registered every time the tree is opened.
+	pub fn set_merge_operator(&mut self, f: impl Fn(Option<V>, V) -> Option<V> + Send + Sync + 'static) {
+		self.merge_operator = Some(Box::new(f));
+	}
+
+	/// #### _Blocking_
+	///
+	/// Read-modify-write the entry in a single descent, Using the registered merge operator.
+	/// Returns the new value, `None` if the entry is deleted (or wasn't inserted).
+	///
+	/// ```ignore
+	/// tree.set_merge_operator(|old, n| Some(old.unwrap_or(0) + n));
+	/// tree.merge(key, 1)?; // counter
+	/// ```
+	pub fn merge(&mut self, key: K, operand: V) -> Result<Option<V>> {
+		let f = match self.merge_operator.take() {
+			Some(f) => f,
+			None => return Err(Error::InvalidInput("No merge operator is registered".into())),
+		};
+		let ret = self.modify(key, |old| f(old, operand));
+		self.merge_operator = Some(f);
+		Ok(ret?.1)
+	}
+
+	/// Replace the entry with `f(old)` in a single descent, `None` means absent.
+	/// Returns the old and the new value.
+	pub(crate) fn modify(&mut self, key: K, f: impl FnOnce(Option<V>) -> Option<V>) -> Result<(Option<V>, Option<V>)> {
+		ensure_ord(&key)?;
+		let (old, new, agg, marge, _) = self._modify(self.root, key, f, true)?;
+		match (old, new) {
+			(None, Some(new)) => {
+				self.len += 1;
+				if let (Some(agg), Some(marge)) = (agg, marge) {
+					self.grow(agg, marge)?;
+				}
+				self.settle()?;
+				self.notify(Event::Inserted { key, new });
+			}
+			(Some(old), Some(new)) if agg.is_some() => self.notify(Event::Updated { key, old, new }),
+			(Some(old), None) => {
+				self.len -= 1;
+				self.shrink()?;
+				self.notify(Event::Deleted { key, old });
+			}
+			_ => {}
+		}
+		Ok((old, new))
+	}
+
+	/// Returns the old value, The new value, The new aggregate of the node (`None` if nothing is changed),
+	/// The split of the node (same as `_set`) and whether the node is underflowed by a deletion.
+	///
+	/// Unlike `_set`, It may also delete the entry. So it has to both split and rebalance the nodes.
+	/// `rightmost` is whether the node is on the right most path of the tree.
+	fn _modify(
+		&mut self,
+		num: u16,
+		key: K,
+		f: impl FnOnce(Option<V>) -> Option<V>,
+		rightmost: bool,
+	) -> Result<(Option<V>, Option<V>, Option<A::Output>, Option<(K, u16, u32, A::Output)>, bool)> {
+		match self.node(num)? {
+			Node::Branch(mut branch) => {
+				let index = branch.lookup(&key);
+				let last = rightmost && index == branch.childs.len() - 1;
+				let (old, new, agg, split, underflow) = self._modify(branch.child_at(index), key, f, last)?;
+				let agg = match agg {
+					Some(agg) => agg,
+					None => return Ok((old, new, None, None, false)),
+				};
+				match (old, new) {
+					(None, Some(_)) => branch.counts[index] += 1,
+					(Some(_), None) => branch.counts[index] -= 1,
+					_ => {}
+				}
+				branch.aggs[index] = agg;
+				let mut marge = None;
+				if let Some((mid, right, count, right_agg)) = split {
+					branch.counts[index] -= count;
+					branch.insert(index, (mid, right, count, right_agg));
+					if branch.is_full() {
+						let len = branch.childs.len();
+						let at = if last { self.fill_branch(len) } else { len / 2 };
+						marge = Some(self.split_branch(&mut branch, at)?);
+					}
+				}
+				if underflow {
+					self.rebalance(&mut branch, index)?;
+				}
+				self.write(num, branch.to_bytes())?;
+				Ok((old, new, Some(branch.aggregate::<V, A>()), marge, underflow && branch.is_underflow()))
+			}
+			Node::Leaf(mut leaf) => {
+				let found = leaf.binary_search(&key);
+				let old = found.ok().map(|i| leaf.entries[i].1);
+				let new = f(old);
+				match (found, new) {
+					// Nothing is changed.
+					(Err(_), None) => return Ok((None, None, None, None, false)),
+					(Ok(_), Some(new)) if old == Some(new) => return Ok((old, old, None, None, false)),
+					(Ok(i), None) => drop(leaf.entries.remove(i)),
+					(Ok(i), Some(new)) => leaf.entries[i].1 = new,
+					(Err(i), Some(new)) => leaf.entries.insert(i, (key, new)),
+				}
+				let mut marge = None;
+				// Same as `_set`, An append to the right most leaf is splitted at the fill factor.
+				if leaf.is_full() {
+					let len = leaf.entries.len();
+					let append = rightmost && leaf.entries[len - 1].0 == key;
+					let at = if append { self.fill(len) } else { len / 2 };
+					marge = Some(self.split_leaf(num, &mut leaf, at)?);
+				}
+				self.write(num, leaf.to_bytes())?;
+				// Only a deletion underflows the leaf, So a new right most leaf isn't rebalanced by the next append.
+				let underflow = new.is_none() && leaf.is_underflow();
+				Ok((old, new, Some(leaf.aggregate::<A>()), marge, underflow))
+			}
+		}
+	}
+}
@@ -1,37 +1,88 @@
 use std::convert::TryInto;
+use flex::{Error, Result};
 
 use bytes::{Buf, BufMut};
 
 use crate::entry::Key;
 
+/// Every file starts with it, Files that are written before the format has a version don't.
+const MAGIC: [u8; 4] = *b"FBPT";
+
+/// Version of the file format, It is bumped whenever the layout of a page (or the metadata) is changed.
+pub const VERSION: u8 = 1;
+
 #[derive(Debug, PartialEq)]
 pub struct MetaInfo {
+	version: u8,
 	key_size: u8,
 	value_size: u8,
+	aggregate_size: u8,
 	block_size: u32,
 }
 
 impl MetaInfo {
-	pub fn new<K: Key, V: Key, const BLOCK_SIZE: usize>() -> Self {
+	/// Encoded size: magic (4) + version (1) + key_size (1) + value_size (1) + aggregate_size (1) + block_size (4)
+	pub const SIZE: usize = 12;
+
+	/// `S` is the output type of the aggregate.
+	pub fn new<K: Key, V: Key, S: Key, const BLOCK_SIZE: usize>() -> Self {
 		Self {
+			version: VERSION,
 			key_size: K::SIZE.try_into().unwrap(),
 			value_size: V::SIZE.try_into().unwrap(),
+			aggregate_size: S::SIZE.try_into().unwrap(),
 			block_size: BLOCK_SIZE as u32,
 		}
 	}
+	pub fn block_size(&self) -> usize {
+		self.block_size as usize
+	}
 	pub fn to_bytes(&self) -> Vec<u8> {
 		let mut v = Vec::new();
+		v.put_slice(&MAGIC);
+		v.put_u8(self.version);
 		v.put_u8(self.key_size);
 		v.put_u8(self.value_size);
+		v.put_u8(self.aggregate_size);
 		v.put_u32_le(self.block_size);
 		v
 	}
-	pub fn from_bytes(mut bytes: &[u8]) -> Self {
-		Self {
+	/// Returns [`Error::FormatMismatch`] if the file isn't written with the current [`VERSION`].
+	pub fn from_bytes(mut bytes: &[u8]) -> Result<Self> {
+		let expected = || format!("format version {}", VERSION);
+		if bytes[..MAGIC.len()] != MAGIC {
+			return Err(Error::FormatMismatch {
+				expected: expected(),
+				found: "a file without format version".into(),
+			});
+		}
+		bytes.advance(MAGIC.len());
+		let version = bytes.get_u8();
+		if version != VERSION {
+			return Err(Error::FormatMismatch {
+				expected: expected(),
+				found: format!("format version {}", version),
+			});
+		}
+		Ok(Self {
+			version,
 			key_size: bytes.get_u8(),
 			value_size: bytes.get_u8(),
+			aggregate_size: bytes.get_u8(),
 			block_size: bytes.get_u32_le(),
+		})
+	}
+	/// Make sure that the file was created with the same `K`, `V`, `S` and `BLOCK_SIZE`.
+	pub fn ensure<K: Key, V: Key, S: Key, const BLOCK_SIZE: usize>(bytes: &[u8]) -> Result<()> {
+		let info = Self::from_bytes(bytes)?;
+		let metainfo = Self::new::<K, V, S, BLOCK_SIZE>();
+		if info != metainfo {
+			return Err(Error::FormatMismatch {
+				expected: format!("{:?}", metainfo),
+				found: format!("{:?}", info),
+			});
 		}
+		Ok(())
 	}
 }
 
@@ -39,6 +90,10 @@ pub struct Metadata {
 	pub is_opened: u8,
 	pub len: u32,
 	pub root: u16,
+	/// Head of the free page list, `0` if there is no free page.
+	pub free: u16,
+	/// In percent, `0` means the default.
+	pub fill_factor: u8,
 }
 
 impl Metadata {
@@ -47,6 +102,8 @@ impl Metadata {
 		v.put_u8(self.is_opened);
 		v.put_u32_le(self.len);
 		v.put_u16_le(self.root);
+		v.put_u16_le(self.free);
+		v.put_u8(self.fill_factor);
 		v
 	}
 
@@ -55,6 +112,8 @@ impl Metadata {
 			is_opened: bytes.get_u8(),
 			len: bytes.get_u32_le(),
 			root: bytes.get_u16_le(),
+			free: bytes.get_u16_le(),
+			fill_factor: bytes.get_u8(),
 		}
 	}
 }
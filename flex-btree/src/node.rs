@@ -1,17 +1,46 @@
 use super::*;
 
-pub enum Node<K, V, const SIZE: usize> {
+pub enum Node<K, V, S, const SIZE: usize> {
 	// This is default node type
 	Leaf(Leaf<K, V, SIZE>),
-	Branch(Branch<K, SIZE>),
+	Branch(Branch<K, S, SIZE>),
 }
 
-impl<K: Key, V: Key, const SIZE: usize> Node<K, V, SIZE> {
-	pub fn from_bytes(bytes: [u8; SIZE]) -> Self {
+impl<K: Key, V: Key, S: Key, const SIZE: usize> Node<K, V, S, SIZE> {
+	/// Returns `None` if the node type is unknown, Or its length exceeds the capacity.
+	pub fn from_bytes(bytes: [u8; SIZE]) -> Option<Self> {
+		Some(match NodeRef::<K, V, S, SIZE>::new(&bytes)? {
+			NodeRef::Leaf(_) => Node::Leaf(Leaf::from_bytes(bytes)),
+			NodeRef::Branch(_) => Node::Branch(Branch::from_bytes(bytes)),
+		})
+	}
+}
+
+/// Same as [`Node`], But borrowed from the raw bytes of the page. See [`LeafRef`] and [`BranchRef`].
+pub enum NodeRef<'a, K, V, S, const SIZE: usize> {
+	Leaf(LeafRef<'a, K, V, SIZE>),
+	Branch(BranchRef<'a, K, S, SIZE>),
+}
+
+impl<'a, K: Key, V: Key, S: Key, const SIZE: usize> NodeRef<'a, K, V, S, SIZE> {
+	/// Returns `None` if the node type is unknown, Or its length exceeds the capacity.
+	pub fn new(bytes: &'a [u8; SIZE]) -> Option<Self> {
 		match bytes[0] {
-			0 => Node::Leaf(Leaf::from_bytes(bytes)),
-			1 => Node::Branch(Branch::from_bytes(bytes)),
-			_ => panic!("Invalid Node Type"),
+			0 => LeafRef::new(bytes).map(NodeRef::Leaf),
+			1 => BranchRef::new(bytes).map(NodeRef::Branch),
+			_ => None,
 		}
 	}
 }
+
+impl<K: Key, V: Key, const SIZE: usize, A: Aggregate<K, V>> BPlusTree<K, V, SIZE, A> {
+	/// #### _Blocking_
+	pub(crate) fn node(&self, num: u16) -> Result<Node<K, V, A::Output, SIZE>> {
+		Node::from_bytes(self.read(num)?).ok_or(Error::Corrupted { page: num as u64 })
+	}
+
+	/// Borrow the node `num` from its page `bytes`, Without decoding it.
+	pub(crate) fn node_ref<'a>(&self, num: u16, bytes: &'a [u8; SIZE]) -> Result<NodeRef<'a, K, V, A::Output, SIZE>> {
+		NodeRef::new(bytes).ok_or(Error::Corrupted { page: num as u64 })
+	}
+}
@@ -0,0 +1,102 @@
+use super::*;
+use std::collections::HashMap;
+
+/// Default [`BPlusTree::pinned_levels`], Only the root is pinned.
+pub const DEFAULT_PINNED_LEVELS: u8 = 1;
+
+/// Pages of the upper levels of the tree, That are kept in memory.
+///
+/// Every write goes through to the file, So the pinned pages never get out of date.
+/// But when a pinned page is splitted, merged or freed, Pages of the pinned levels are changed.
+/// Then they are marked as `stale`, And loaded again at the end of the operation.
+pub(crate) struct Pinned<const SIZE: usize> {
+	pub levels: u8,
+	pages: HashMap<u16, [u8; SIZE]>,
+	stale: bool,
+}
+
+impl<const SIZE: usize> Pinned<SIZE> {
+	pub fn new() -> Self {
+		Self {
+			levels: DEFAULT_PINNED_LEVELS,
+			pages: HashMap::new(),
+			stale: true,
+		}
+	}
+}
+
+impl<K: Key, V: Key, const SIZE: usize, A: Aggregate<K, V>> BPlusTree<K, V, SIZE, A> {
+	/// Number of levels from the root, That are kept in memory. See [`BPlusTree::pin_levels`].
+	pub fn pinned_levels(&self) -> u8 {
+		self.pinned.levels
+	}
+
+	/// #### _Blocking_
+	///
+	/// Keep the top `levels` of the tree in memory, So a lookup doesn't read their pages.
+	/// `1` (the default) pins only the root, `0` disables pinning.
+	///
+	/// Each level is about `capacity` times larger than the level above it,
+	/// So pinning more than 2 or 3 levels (of a large tree) costs a lot of memory.
+	pub fn pin_levels(&mut self, levels: u8) -> Result<()> {
+		self.pinned.levels = levels;
+		self.repin()
+	}
+
+	/// #### _Blocking_
+	pub(crate) fn read(&self, num: u16) -> Result<[u8; SIZE]> {
+		match self.pinned.pages.get(&num) {
+			Some(&bytes) => Ok(bytes),
+			None => Ok(self.pages.read(num as u64)?),
+		}
+	}
+
+	/// #### _Blocking_
+	///
+	/// Write the page through, And update its pinned copy.
+	pub(crate) fn write(&mut self, num: u16, bytes: [u8; SIZE]) -> Result<()> {
+		self.pages.write(num as u64, bytes)?;
+		self.writes += 1;
+		if let Some(pinned) = self.pinned.pages.get_mut(&num) {
+			// Node type and keys len of a branch (Or `next` of a leaf) is changed, When the page is splitted, merged or freed.
+			if pinned[..3] != bytes[..3] {
+				self.pinned.stale = true;
+			}
+			*pinned = bytes;
+		}
+		Ok(())
+	}
+
+	/// The height of the tree is changed.
+	pub(crate) fn unpin(&mut self) {
+		self.pinned.stale = true;
+	}
+
+	/// #### _Blocking_
+	///
+	/// Load the pinned levels again, If they are stale.
+	pub(crate) fn settle(&mut self) -> Result<()> {
+		if self.pinned.stale {
+			self.repin()?;
+		}
+		Ok(())
+	}
+
+	fn repin(&mut self) -> Result<()> {
+		self.pinned.pages.clear();
+		let mut level = vec![self.root];
+		for _ in 0..self.pinned.levels {
+			let mut next = Vec::new();
+			for num in level {
+				let bytes = self.pages.read(num as u64)?;
+				if let NodeRef::Branch(branch) = self.node_ref(num, &bytes)? {
+					next.extend((0..branch.len()).map(|i| branch.child(i)));
+				}
+				self.pinned.pages.insert(num, bytes);
+			}
+			level = next;
+		}
+		self.pinned.stale = false;
+		Ok(())
+	}
+}
@@ -0,0 +1,78 @@
+use super::*;
+
+impl<K: Key, V: Key, const SIZE: usize, A: Aggregate<K, V>> BPlusTree<K, V, SIZE, A> {
+	/// #### _Blocking_
+	///
+	/// Remove and return the entry with the smallest key, In a single descent.
+	pub fn pop_first(&mut self) -> Result<Option<(K, V)>> {
+		self.pop(false, |_, _| true)
+	}
+
+	/// #### _Blocking_
+	///
+	/// Remove and return the entry with the largest key, In a single descent.
+	pub fn pop_last(&mut self) -> Result<Option<(K, V)>> {
+		self.pop(true, |_, _| true)
+	}
+
+	/// #### _Blocking_
+	///
+	/// Same as [`BPlusTree::pop_first`], But only if `pred` returns `true` for the first entry.
+	///
+	/// ```ignore
+	/// // Take a job, That is due.
+	/// tree.pop_first_if(|due, _| *due <= now)?;
+	/// ```
+	pub fn pop_first_if(&mut self, pred: impl FnOnce(&K, &V) -> bool) -> Result<Option<(K, V)>> {
+		self.pop(false, pred)
+	}
+
+	fn pop(&mut self, last: bool, pred: impl FnOnce(&K, &V) -> bool) -> Result<Option<(K, V)>> {
+		let (ret, _, _) = self._pop(self.root, last, pred)?;
+		if let Some((key, old)) = ret {
+			self.len -= 1;
+			self.shrink()?;
+			self.notify(Event::Deleted { key, old });
+		}
+		Ok(ret)
+	}
+
+	/// Same as `_delete`, But it removes the first (or last) entry of the subtree.
+	fn _pop(
+		&mut self,
+		num: u16,
+		last: bool,
+		pred: impl FnOnce(&K, &V) -> bool,
+	) -> Result<(Option<(K, V)>, A::Output, bool)> {
+		match self.node(num)? {
+			Node::Leaf(mut leaf) => {
+				let index = match (last, leaf.entries.len()) {
+					(_, 0) => return Ok((None, A::identity(), false)),
+					(true, len) => len - 1,
+					(false, _) => 0,
+				};
+				let (key, value) = &leaf.entries[index];
+				if !pred(key, value) {
+					return Ok((None, A::identity(), false));
+				}
+				let entry = leaf.entries.remove(index);
+				self.write(num, leaf.to_bytes())?;
+				Ok((Some(entry), leaf.aggregate::<A>(), leaf.is_underflow()))
+			}
+			Node::Branch(mut branch) => {
+				let index = if last { branch.childs.len() - 1 } else { 0 };
+				let (ret, agg, underflow) = self._pop(branch.childs[index], last, pred)?;
+				if ret.is_none() {
+					return Ok((None, agg, false));
+				}
+				branch.counts[index] -= 1;
+				branch.aggs[index] = agg;
+				if underflow {
+					self.rebalance(&mut branch, index)?;
+				}
+				self.write(num, branch.to_bytes())?;
+				Ok((ret, branch.aggregate::<V, A>(), branch.is_underflow()))
+			}
+		}
+	}
+}
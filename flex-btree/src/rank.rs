@@ -0,0 +1,79 @@
+use super::*;
+use std::ops::{Bound, RangeBounds};
+
+impl<K: Key, V: Key, const SIZE: usize, A: Aggregate<K, V>> BPlusTree<K, V, SIZE, A> {
+	/// #### _Blocking_
+	///
+	/// Returns the entry at `index` in key order.
+	/// It uses subtree counts of the branches, So it only reads a single path from the root.
+	pub fn nth(&self, mut index: u32) -> Result<Option<(K, V)>> {
+		if index >= self.len {
+			return Ok(None);
+		}
+		let mut num = self.root;
+		loop {
+			num = match self.node(num)? {
+				Node::Branch(branch) => {
+					let mut child = branch.childs.len() - 1;
+					for (i, &count) in branch.counts.iter().enumerate() {
+						if index < count {
+							child = i;
+							break;
+						}
+						index -= count;
+					}
+					branch.childs[child]
+				}
+				Node::Leaf(leaf) => return Ok(leaf.entries.get(index as usize).copied()),
+			}
+		}
+	}
+
+	/// #### _Blocking_
+	///
+	/// Returns the number of entries, Whose key is less than `key`.
+	pub fn rank(&self, key: &K) -> Result<u32> {
+		ensure_ord(key)?;
+		self._rank(key, false)
+	}
+
+	/// #### _Blocking_
+	///
+	/// Returns the number of entries within the `range`.
+	pub fn count_range(&self, range: impl RangeBounds<K>) -> Result<u32> {
+		ensure_bounds(&range)?;
+		let start = match range.start_bound() {
+			Bound::Included(key) => self._rank(key, false)?,
+			Bound::Excluded(key) => self._rank(key, true)?,
+			Bound::Unbounded => 0,
+		};
+		let end = match range.end_bound() {
+			Bound::Included(key) => self._rank(key, true)?,
+			Bound::Excluded(key) => self._rank(key, false)?,
+			Bound::Unbounded => self.len,
+		};
+		Ok(end.saturating_sub(start))
+	}
+
+	/// Returns the number of entries, Whose key is less than (or equal to, if `inclusive`) `key`.
+	fn _rank(&self, key: &K, inclusive: bool) -> Result<u32> {
+		let mut rank = 0;
+		let mut num = self.root;
+		loop {
+			num = match self.node(num)? {
+				Node::Branch(branch) => {
+					let index = branch.lookup(key);
+					rank += branch.counts[..index].iter().sum::<u32>();
+					branch.child_at(index)
+				}
+				Node::Leaf(leaf) => {
+					return Ok(rank
+						+ match leaf.binary_search(key) {
+							Ok(i) if inclusive => i + 1,
+							Ok(i) | Err(i) => i,
+						} as u32)
+				}
+			}
+		}
+	}
+}
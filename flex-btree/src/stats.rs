@@ -0,0 +1,93 @@
+use super::*;
+
+/// Statistics of a single level of the tree. Level `0` is the root.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Level {
+	pub pages: u64,
+	/// Number of childs for branch level, And number of `(K, V)` pairs for leaf level.
+	pub entries: u64,
+}
+
+/// Space usage of the tree, Returned by [`BPlusTree::stats`].
+#[derive(Debug, Clone, Default)]
+pub struct Stats {
+	pub height: usize,
+	pub leaf_pages: u64,
+	pub branch_pages: u64,
+	/// Average fill factor (`0.0..=1.0`) of all nodes.
+	pub avg_fill: f64,
+	/// Minimum fill factor of all nodes, Except root node.
+	pub min_fill: f64,
+	pub levels: Vec<Level>,
+	/// Length of the free page list.
+	pub free_pages: u64,
+	/// File size in bytes.
+	pub file_size: u64,
+	/// Average distance (in pages) on disk between logically consecutive leaves.
+	/// `1.0` means leaves are laid out sequentially.
+	pub leaf_distance: f64,
+}
+
+impl<K: Key, V: Key, const SIZE: usize, A: Aggregate<K, V>> BPlusTree<K, V, SIZE, A> {
+	/// #### _Blocking_
+	///
+	/// Walk the tree level by level, And collect its space usage.
+	pub fn stats(&self) -> Result<Stats> {
+		let mut stats = Stats {
+			min_fill: 1.0,
+			..Stats::default()
+		};
+		let mut fill_sum = 0.0;
+		let mut level = vec![self.root];
+
+		while !level.is_empty() {
+			let mut next_level = Vec::new();
+			let mut info = Level::default();
+			for &num in level.iter() {
+				let fill = match self.node(num)? {
+					Node::Branch(branch) => {
+						stats.branch_pages += 1;
+						info.entries += branch.childs.len() as u64;
+						next_level.extend_from_slice(&branch.childs);
+						branch.childs.len() as f64 / Branch::<K, A::Output, SIZE>::capacity() as f64
+					}
+					Node::Leaf(leaf) => {
+						stats.leaf_pages += 1;
+						info.entries += leaf.entries.len() as u64;
+						leaf.entries.len() as f64 / Leaf::<K, V, SIZE>::capacity() as f64
+					}
+				};
+				info.pages += 1;
+				fill_sum += fill;
+				if num != self.root && fill < stats.min_fill {
+					stats.min_fill = fill;
+				}
+			}
+			// Leaves are collected in key order, So the last level gives us their layout on disk.
+			if next_level.is_empty() && level.len() > 1 {
+				let distance: u64 = level
+					.windows(2)
+					.map(|w| (w[0] as i64 - w[1] as i64).unsigned_abs())
+					.sum();
+				stats.leaf_distance = distance as f64 / (level.len() - 1) as f64;
+			}
+			stats.levels.push(info);
+			level = next_level;
+		}
+
+		let mut free = self.free;
+		while free != 0 {
+			// A free list of a corrupted file may have a cycle, It can't be longer than the file.
+			if stats.free_pages >= self.pages.len() {
+				return Err(Error::Corrupted { page: free as u64 });
+			}
+			stats.free_pages += 1;
+			let page = self.read(free)?;
+			free = u16::from_le_bytes([page[1], page[2]]);
+		}
+		stats.height = stats.levels.len();
+		stats.avg_fill = fill_sum / (stats.leaf_pages + stats.branch_pages) as f64;
+		stats.file_size = self.pages.len() * SIZE as u64;
+		Ok(stats)
+	}
+}
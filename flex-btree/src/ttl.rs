@@ -0,0 +1,225 @@
+use super::*;
+use std::convert::TryFrom;
+use std::ffi::OsString;
+use std::fmt;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use SetOption::*;
+
+/// Value with its expiration time, In milliseconds since UNIX epoch. `0` means it never expires.
+#[derive(Debug, Clone, Copy, PartialEq, PartialOrd)]
+pub(crate) struct Expiring<V> {
+	expires_at: u64,
+	value: V,
+}
+
+impl<V: Key> Key for Expiring<V> {
+	const SIZE: usize = 8 + V::SIZE;
+	fn to_bytes(self) -> Vec<u8> {
+		[self.expires_at.to_bytes(), self.value.to_bytes()].concat()
+	}
+	fn from_bytes(bytes: &[u8]) -> Self {
+		Self {
+			expires_at: u64::from_bytes(&bytes[..8]),
+			value: V::from_bytes(&bytes[8..]),
+		}
+	}
+}
+
+impl<V> Expiring<V> {
+	fn is_expired(&self, now: u64) -> bool {
+		self.expires_at != 0 && self.expires_at <= now
+	}
+}
+
+fn now() -> u64 {
+	let now = SystemTime::now()
+		.duration_since(UNIX_EPOCH)
+		.expect("System time is before UNIX epoch");
+	u64::try_from(now.as_millis()).unwrap_or(u64::MAX)
+}
+
+/// A [`BPlusTree`] whose entries can expire.
+///
+/// Expired entries are hidden immediately, But their space is only reclaimed by [`TtlTree::expire`].
+/// Expiration times are kept in a secondary index, Ordered by time. It is stored next to the
+/// tree file, With `.ttl` suffix.
+pub struct TtlTree<K, V, const SIZE: usize> {
+	tree: BPlusTree<K, Expiring<V>, SIZE>,
+	/// `(expires_at, key)`
+	index: BPlusTree<(u64, K), (), SIZE>,
+}
+
+impl<K: Key, V: Key, const SIZE: usize> TtlTree<K, V, SIZE> {
+	/// #### _Blocking_
+	pub fn open(path: impl AsRef<Path>) -> Result<Self> {
+		let mut index_path = OsString::from(path.as_ref());
+		index_path.push(".ttl");
+		Ok(Self {
+			tree: BPlusTree::open(path)?,
+			index: BPlusTree::open(index_path)?,
+		})
+	}
+
+	/// Number of entries, Including expired entries that are not reclaimed yet.
+	pub fn len(&self) -> u32 {
+		self.tree.len()
+	}
+
+	/// #### _Blocking_
+	///
+	/// Set an entry that never expires.
+	pub fn set(&mut self, key: K, value: V, opt: SetOption) -> Result<Option<V>> {
+		self._set(key, value, 0, opt)
+	}
+
+	/// #### _Blocking_
+	///
+	/// Insert or update an entry that expires after `ttl`.
+	/// Returns the old value, If it isn't expired.
+	///
+	/// A `ttl` that is too large for the clock (like [`Duration::MAX`]) never expires in practice.
+	pub fn set_with_ttl(&mut self, key: K, value: V, ttl: Duration) -> Result<Option<V>> {
+		let ttl = u64::try_from(ttl.as_millis()).unwrap_or(u64::MAX);
+		self._set(key, value, now().saturating_add(ttl), UpdateOrInsert)
+	}
+
+	fn _set(&mut self, key: K, value: V, expires_at: u64, opt: SetOption) -> Result<Option<V>> {
+		let now = now();
+		let entry = Expiring { expires_at, value };
+		match opt {
+			UpdateOrInsert => {
+				let old = self.tree.set(key, entry, UpdateOrInsert)?;
+				self.reindex(key, old.map(|old| old.expires_at), expires_at)?;
+				Ok(old.filter(|old| !old.is_expired(now)).map(|old| old.value))
+			}
+			// An expired entry is treated as absent.
+			FindOrInsert => match self.tree.set(key, entry, FindOrInsert)? {
+				Some(old) if old.is_expired(now) => {
+					self._set(key, value, expires_at, UpdateOrInsert)?;
+					Ok(None)
+				}
+				Some(old) => Ok(Some(old.value)),
+				None => {
+					self.reindex(key, None, expires_at)?;
+					Ok(None)
+				}
+			},
+		}
+	}
+
+	/// Move the key from `old` expiration time to the `new` one, in the index.
+	fn reindex(&mut self, key: K, old: Option<u64>, new: u64) -> Result<()> {
+		if old == Some(new) {
+			return Ok(());
+		}
+		if let Some(old) = old.filter(|&old| old != 0) {
+			self.index.delete(&(old, key))?;
+		}
+		if new != 0 {
+			self.index.set((new, key), (), UpdateOrInsert)?;
+		}
+		Ok(())
+	}
+
+	/// #### _Blocking_
+	pub fn get(&self, opt: Get<K>) -> Result<TtlView<K, V, SIZE>> {
+		Ok(TtlView {
+			view: self.tree.get(opt)?,
+			now: now(),
+		})
+	}
+
+	/// #### _Blocking_
+	///
+	/// Returns the deleted entry, If it isn't expired.
+	pub fn delete(&mut self, key: &K) -> Result<Option<(K, V)>> {
+		let (key, entry) = match self.tree.delete(key)? {
+			Some(ret) => ret,
+			None => return Ok(None),
+		};
+		if entry.expires_at != 0 {
+			self.index.delete(&(entry.expires_at, key))?;
+		}
+		if entry.is_expired(now()) {
+			return Ok(None);
+		}
+		Ok(Some((key, entry.value)))
+	}
+
+	/// #### _Blocking_
+	///
+	/// Reclaim the space of expired entries, Returns the number of reclaimed entries.
+	///
+	/// It only reads the expired part of the index, So it never needs a full scan.
+	/// It is meant to be called periodically, For example from a background thread.
+	pub fn expire(&mut self) -> Result<u32> {
+		let now = now();
+		let mut expired = Vec::new();
+		let mut view = self.index.get(Get::First)?;
+		'scan: loop {
+			for &((expires_at, key), ()) in view.iter() {
+				if expires_at > now {
+					break 'scan;
+				}
+				expired.push((expires_at, key));
+			}
+			if !view.next()? {
+				break;
+			}
+		}
+		drop(view);
+
+		let mut count = 0;
+		for (expires_at, key) in expired {
+			self.index.delete(&(expires_at, key))?;
+			// Make sure that the entry wasn't updated with a new expiration time.
+			let current = self.tree.get(Get::Exact(key))?.find(&key).map(|(_, e)| e.expires_at);
+			if current == Some(expires_at) {
+				self.tree.delete(&key)?;
+				count += 1;
+			}
+		}
+		Ok(count)
+	}
+}
+
+/// Same as [`View`], But expired entries are hidden.
+pub struct TtlView<'a, K, V, const SIZE: usize> {
+	view: View<'a, K, Expiring<V>, SIZE>,
+	now: u64,
+}
+
+impl<K: Key, V: Key, const SIZE: usize> TtlView<'_, K, V, SIZE> {
+	/// #### _Blocking_
+	pub fn next(&mut self) -> Result<bool> {
+		self.view.next()
+	}
+
+	/// #### _Blocking_
+	pub fn prev(&mut self) -> Result<bool> {
+		self.view.prev()
+	}
+
+	/// Entries of the current leaf, That aren't expired.
+	pub fn iter(&self) -> impl Iterator<Item = (K, V)> + '_ {
+		let now = self.now;
+		self.view
+			.iter()
+			.filter(move |(_, e)| !e.is_expired(now))
+			.map(|&(k, e)| (k, e.value))
+	}
+
+	pub fn find(&self, key: &K) -> Option<V> {
+		self.view
+			.find(key)
+			.filter(|(_, e)| !e.is_expired(self.now))
+			.map(|(_, e)| e.value)
+	}
+}
+
+impl<K: Key, V: Key, const SIZE: usize> fmt::Debug for TtlView<'_, K, V, SIZE> {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		f.debug_list().entries(self.iter()).finish()
+	}
+}
@@ -0,0 +1,278 @@
+use super::*;
+use std::mem::replace;
+
+/// A structural problem found by [`verify`].
+#[derive(Debug, Clone, PartialEq)]
+pub enum Problem<K> {
+	/// Page number is out of bound, Or points to the metadata page.
+	InvalidPage { page: u16 },
+	/// Page doesn't contain a valid node.
+	Corrupted { page: u16 },
+	/// Keys of the node are not in ascending order.
+	Unsorted { page: u16 },
+	/// Key is outside of the range that is allowed by the parent's separator keys.
+	OutOfBound { page: u16, key: K },
+	/// `next` / `prev` link of a leaf doesn't point to its logical neighbour.
+	BrokenLink {
+		page: u16,
+		next: u16,
+		prev: u16,
+		expected_next: u16,
+		expected_prev: u16,
+	},
+	/// Leaf isn't at the same depth as the other leaves.
+	UnevenDepth { page: u16, depth: usize, expected: usize },
+	/// Node (except root node and the right most node of each level) has fewer entries (or childs) than the minimum.
+	///
+	/// The right most nodes are filled by appends, See [`BPlusTree::set_fill_factor`](crate::BPlusTree::set_fill_factor).
+	Underflow { page: u16, len: usize, min: usize },
+	/// Page is referenced more than once, Or it is reachable from the root and the free list.
+	Duplicate { page: u16 },
+	/// Page isn't reachable from the root or the free list.
+	Unreachable { page: u16 },
+	/// Stored aggregate of a child doesn't match the aggregate of its subtree.
+	AggregateMismatch { page: u16, child: u16 },
+	/// Subtree count of a child doesn't match the number of entries in that subtree.
+	CountMismatch {
+		page: u16,
+		child: u16,
+		expected: u32,
+		found: u64,
+	},
+	/// `Metadata.len` doesn't match the number of entries.
+	LenMismatch { expected: u32, found: u64 },
+}
+
+/// Result of a structural check.
+#[derive(Debug)]
+pub struct Report<K> {
+	/// Total number of pages in the file, Including the metadata page.
+	pub pages: u64,
+	pub problems: Vec<Problem<K>>,
+}
+
+impl<K> Report<K> {
+	pub fn is_ok(&self) -> bool {
+		self.problems.is_empty()
+	}
+}
+
+/// #### _Blocking_
+///
+/// Check the structure of the file at `path`, Without opening it as a [`BPlusTree`].
+/// `A` is the aggregate of the tree, Use `()` if the tree has no aggregate.
+///
+/// It ignores `is_opened` flag, So it can be used on a file that wasn't closed properly.
+/// But don't use it on a file that is currently opened, Because `len` and `root` are only persisted on drop.
+pub fn verify<K: Key, V: Key, const SIZE: usize, A: Aggregate<K, V>>(
+	path: impl AsRef<Path>,
+) -> Result<Report<K>> {
+	let pages = Pages::<SIZE>::open(File::open(path)?)?;
+	let raw_meta = pages.read(0)?;
+	MetaInfo::ensure::<K, V, A::Output, SIZE>(&raw_meta[..MetaInfo::SIZE])?;
+	let metadata = Metadata::from_bytes(&raw_meta[MetaInfo::SIZE..]);
+	check::<K, V, A, SIZE>(&pages, metadata.root, metadata.free, metadata.len)
+}
+
+pub(crate) fn check<K: Key, V: Key, A: Aggregate<K, V>, const SIZE: usize>(
+	pages: &Pages<SIZE>,
+	root: u16,
+	free: u16,
+	len: u32,
+) -> Result<Report<K>> {
+	let total = pages.len();
+	let mut checker = Checker::<K, V, A, SIZE> {
+		pages,
+		seen: vec![false; total as usize],
+		leaves: Vec::new(),
+		depth: None,
+		count: 0,
+		problems: Vec::new(),
+		_marker: PhantomData,
+	};
+	checker.walk(root, 0, None, None)?;
+	checker.check_links();
+	checker.walk_free_list(free)?;
+
+	let mut problems = checker.problems;
+	for page in 1..total {
+		if !checker.seen[page as usize] {
+			problems.push(Problem::Unreachable { page: page as u16 });
+		}
+	}
+	if checker.count != len as u64 {
+		problems.push(Problem::LenMismatch {
+			expected: len,
+			found: checker.count,
+		});
+	}
+	Ok(Report {
+		pages: total,
+		problems,
+	})
+}
+
+struct Checker<'a, K, V, A, const SIZE: usize> {
+	pages: &'a Pages<SIZE>,
+	seen: Vec<bool>,
+	/// Every leaf in key order: `(page, prev, next)`
+	leaves: Vec<(u16, u16, u16)>,
+	/// Depth of the first leaf.
+	depth: Option<usize>,
+	count: u64,
+	problems: Vec<Problem<K>>,
+	_marker: PhantomData<(V, A)>,
+}
+
+impl<K: Key, V: Key, A: Aggregate<K, V>, const SIZE: usize> Checker<'_, K, V, A, SIZE> {
+	/// `lower` is inclusive and `upper` is exclusive.
+	///
+	/// Returns the number of entries and the aggregate of this subtree, Or `None` if the page can't be checked.
+	fn walk(
+		&mut self,
+		page: u16,
+		depth: usize,
+		lower: Option<K>,
+		upper: Option<K>,
+	) -> Result<Option<(u64, A::Output)>> {
+		if !self.visit(page) {
+			return Ok(None);
+		}
+		let bytes = self.pages.read(page as u64)?;
+		let is_root = depth == 0;
+		match bytes[0] {
+			0 => {
+				// Node type (1) + next (2) + prev (2)
+				let len = u16::from_le_bytes([bytes[5], bytes[6]]) as usize;
+				if len > Leaf::<K, V, SIZE>::capacity() {
+					self.problems.push(Problem::Corrupted { page });
+					return Ok(None);
+				}
+				let leaf = Leaf::<K, V, SIZE>::from_bytes(bytes);
+				let keys: Vec<K> = leaf.entries.iter().map(|(k, _)| *k).collect();
+				self.check_keys(page, &keys, lower, upper);
+
+				let min = Leaf::<K, V, SIZE>::capacity() / 2;
+				if !is_root && upper.is_some() && len < min {
+					self.problems.push(Problem::Underflow { page, len, min });
+				}
+				match self.depth {
+					None => self.depth = Some(depth),
+					Some(expected) if expected != depth => {
+						self.problems.push(Problem::UnevenDepth {
+							page,
+							depth,
+							expected,
+						});
+					}
+					_ => {}
+				}
+				self.leaves.push((page, leaf.prev, leaf.next));
+				self.count += len as u64;
+				Ok(Some((len as u64, leaf.aggregate::<A>())))
+			}
+			1 => {
+				// Node type (1)
+				let len = u16::from_le_bytes([bytes[1], bytes[2]]) as usize + 1;
+				if len > Branch::<K, A::Output, SIZE>::capacity() {
+					self.problems.push(Problem::Corrupted { page });
+					return Ok(None);
+				}
+				let branch = Branch::<K, A::Output, SIZE>::from_bytes(bytes);
+				self.check_keys(page, &branch.keys, lower, upper);
+
+				let min = if is_root {
+					2
+				} else if upper.is_none() {
+					1
+				} else {
+					Branch::<K, A::Output, SIZE>::capacity() / 2
+				};
+				if len < min {
+					self.problems.push(Problem::Underflow { page, len, min });
+				}
+				let mut total = 0;
+				let mut total_agg = A::identity();
+				for (i, &child) in branch.childs.iter().enumerate() {
+					let lower = if i == 0 { lower } else { Some(branch.keys[i - 1]) };
+					let upper = branch.keys.get(i).copied().or(upper);
+					let (found, agg) = match self.walk(child, depth + 1, lower, upper)? {
+						Some(ret) => ret,
+						None => continue,
+					};
+					if agg != branch.aggs[i] {
+						self.problems.push(Problem::AggregateMismatch { page, child });
+					}
+					if found != branch.counts[i] as u64 {
+						self.problems.push(Problem::CountMismatch {
+							page,
+							child,
+							expected: branch.counts[i],
+							found,
+						});
+					}
+					total += found;
+					total_agg = A::combine(total_agg, agg);
+				}
+				Ok(Some((total, total_agg)))
+			}
+			_ => {
+				self.problems.push(Problem::Corrupted { page });
+				Ok(None)
+			}
+		}
+	}
+
+	/// Mark the page as seen, Returns `false` if the page is invalid or already seen.
+	fn visit(&mut self, page: u16) -> bool {
+		if page == 0 || page as u64 >= self.pages.len() {
+			self.problems.push(Problem::InvalidPage { page });
+			return false;
+		}
+		if replace(&mut self.seen[page as usize], true) {
+			self.problems.push(Problem::Duplicate { page });
+			return false;
+		}
+		true
+	}
+
+	fn walk_free_list(&mut self, mut page: u16) -> Result<()> {
+		while page != 0 && self.visit(page) {
+			let bytes = self.pages.read(page as u64)?;
+			if bytes[0] != 2 {
+				self.problems.push(Problem::Corrupted { page });
+				break;
+			}
+			page = u16::from_le_bytes([bytes[1], bytes[2]]);
+		}
+		Ok(())
+	}
+
+	fn check_keys(&mut self, page: u16, keys: &[K], lower: Option<K>, upper: Option<K>) {
+		if keys.windows(2).any(|w| !(w[0] < w[1])) {
+			self.problems.push(Problem::Unsorted { page });
+		}
+		let out_of_bound = keys.iter().find(|&&key| {
+			lower.map_or(false, |l| !(l <= key)) || upper.map_or(false, |u| !(key < u))
+		});
+		if let Some(&key) = out_of_bound {
+			self.problems.push(Problem::OutOfBound { page, key });
+		}
+	}
+
+	fn check_links(&mut self) {
+		for (i, &(page, prev, next)) in self.leaves.iter().enumerate() {
+			let expected_prev = if i == 0 { 0 } else { self.leaves[i - 1].0 };
+			let expected_next = self.leaves.get(i + 1).map_or(0, |l| l.0);
+			if prev != expected_prev || next != expected_next {
+				self.problems.push(Problem::BrokenLink {
+					page,
+					next,
+					prev,
+					expected_next,
+					expected_prev,
+				});
+			}
+		}
+	}
+}
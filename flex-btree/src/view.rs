@@ -1,47 +1,74 @@
 use super::*;
+use std::cell::OnceCell;
 use std::{fmt, ops::Deref};
 
+/// A leaf of the tree, That can move to its siblings.
+///
+/// The leaf is kept as the raw bytes of its page, [`View::find`] searches them in place.
+/// Entries are decoded only when the view is used as a `Vec` (by `Deref`).
 pub struct View<'a, K, V, const SIZE: usize> {
-	pub(super) pages: &'a Pages<SIZE>,
-	pub(super) leaf: Leaf<K, V, SIZE>,
+	pages: &'a Pages<SIZE>,
+	bytes: [u8; SIZE],
+	entries: OnceCell<Vec<(K, V)>>,
 }
 
-impl<K: Key, V: Key, const SIZE: usize> View<'_, K, V, SIZE> {
+impl<'a, K: Key, V: Key, const SIZE: usize> View<'a, K, V, SIZE> {
+	/// `bytes` must be a valid leaf.
+	pub(crate) fn new(pages: &'a Pages<SIZE>, bytes: [u8; SIZE]) -> Self {
+		Self {
+			pages,
+			bytes,
+			entries: OnceCell::new(),
+		}
+	}
+
+	fn leaf(&self) -> LeafRef<K, V, SIZE> {
+		LeafRef::new_unchecked(&self.bytes)
+	}
+
 	/// #### _Blocking_
 	pub fn next(&mut self) -> Result<bool> {
-		self._fetch(self.leaf.next as u64)
+		self._fetch(self.leaf().next())
 	}
 
 	/// #### _Blocking_
 	pub fn prev(&mut self) -> Result<bool> {
-		self._fetch(self.leaf.prev as u64)
+		self._fetch(self.leaf().prev())
 	}
 
+	/// `NaN` is never found.
 	pub fn find_idx(&self, key: &K) -> Option<usize> {
-		self.leaf.binary_search(key).ok()
+		ensure_ord(key).ok()?;
+		self.leaf().binary_search(key).ok()
 	}
 
-	pub fn find(&self, key: &K) -> Option<&(K, V)> {
-		self.leaf.entries.get(self.find_idx(key)?)
+	/// Only the matching entry is decoded.
+	pub fn find(&self, key: &K) -> Option<(K, V)> {
+		let index = self.find_idx(key)?;
+		Some((self.leaf().key(index), self.leaf().value(index)))
 	}
 
-	fn _fetch(&mut self, num: u64) -> Result<bool> {
+	fn _fetch(&mut self, num: u16) -> Result<bool> {
 		if num == 0 {
 			return Ok(false);
 		}
-		self.leaf = Leaf::from_bytes(self.pages.read(num)?);
+		let bytes = self.pages.read(num as u64)?;
+		if bytes[0] != 0 || LeafRef::<K, V, SIZE>::new(&bytes).is_none() {
+			return Err(Error::Corrupted { page: num as u64 });
+		}
+		*self = Self::new(self.pages, bytes);
 		Ok(true)
 	}
 }
 
-impl<K, V, const SIZE: usize> Deref for View<'_, K, V, SIZE> {
+impl<K: Key, V: Key, const SIZE: usize> Deref for View<'_, K, V, SIZE> {
 	type Target = Vec<(K, V)>;
 	fn deref(&self) -> &Self::Target {
-		&self.leaf.entries
+		self.entries.get_or_init(|| Leaf::<K, V, SIZE>::from_bytes(self.bytes).entries)
 	}
 }
-impl<K: fmt::Debug, V: fmt::Debug, const SIZE: usize> fmt::Debug for View<'_, K, V, SIZE> {
+impl<K: Key, V: Key, const SIZE: usize> fmt::Debug for View<'_, K, V, SIZE> {
 	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-		f.debug_list().entries(&self.leaf.entries).finish()
+		f.debug_list().entries(self.iter()).finish()
 	}
 }
@@ -0,0 +1,48 @@
+use super::*;
+use std::ops::{Bound, RangeBounds};
+use std::sync::mpsc::{channel, Receiver, Sender};
+
+/// A change of an entry, See [`BPlusTree::watch`].
+#[derive(Debug, Clone, PartialEq)]
+pub enum Event<K, V> {
+	Inserted { key: K, new: V },
+	Updated { key: K, old: V, new: V },
+	Deleted { key: K, old: V },
+}
+
+impl<K, V> Event<K, V> {
+	pub fn key(&self) -> &K {
+		match self {
+			Event::Inserted { key, .. } | Event::Updated { key, .. } | Event::Deleted { key, .. } => key,
+		}
+	}
+}
+
+pub(crate) struct Watcher<K, V> {
+	range: (Bound<K>, Bound<K>),
+	sender: Sender<Event<K, V>>,
+}
+
+impl<K: Key, V: Key, const SIZE: usize, A: Aggregate<K, V>> BPlusTree<K, V, SIZE, A> {
+	/// Subscribe to the changes of the entries within the `range`.
+	///
+	/// Events are sent after the change is written, In the same order as they are made.
+	/// The watcher is removed once its receiver is dropped.
+	pub fn watch(&mut self, range: impl RangeBounds<K>) -> Receiver<Event<K, V>> {
+		let (sender, receiver) = channel();
+		let range = (range.start_bound().cloned(), range.end_bound().cloned());
+		self.watchers.push(Watcher { range, sender });
+		receiver
+	}
+
+	pub(crate) fn notify(&mut self, event: Event<K, V>) {
+		let key = event.key();
+		self.watchers
+			.retain(|w| !w.range.contains(key) || w.sender.send(event.clone()).is_ok());
+	}
+
+	/// Returns whether any watcher is interested in the `key`.
+	pub(crate) fn is_watched(&self, key: &K) -> bool {
+		self.watchers.iter().any(|w| w.range.contains(key))
+	}
+}
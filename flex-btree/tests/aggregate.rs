@@ -0,0 +1,82 @@
+use std::{fs::remove_file, io::Result};
+
+use flex_btree::{BPlusTree, Count, Max, Min, SetOption, Sum};
+
+#[test]
+fn sum() -> Result<()> {
+	let _ = remove_file("aggregate_sum");
+	{
+		let mut tree = BPlusTree::<u32, u64, 128, Sum>::open("aggregate_sum")?;
+		for i in 0..2000u32 {
+			let key = i * 7919 % 2000;
+			tree.set(key, key as u64, SetOption::UpdateOrInsert)?;
+		}
+		assert_eq!(tree.aggregate(..)?, (0..2000).sum());
+		assert_eq!(tree.aggregate(100..200)?, (100..200).sum());
+		assert_eq!(tree.aggregate(100..=200)?, (100..=200).sum());
+		assert_eq!(tree.aggregate(1990..)?, (1990..2000).sum());
+		assert_eq!(tree.aggregate(5000..)?, 0);
+
+		// Aggregates are maintained by updates and deletes.
+		tree.set(151, 1_000_000, SetOption::UpdateOrInsert)?;
+		for key in (0..2000).step_by(3) {
+			tree.delete(&key)?;
+		}
+		let expected: u128 = (100..200).filter(|k| k % 3 != 0).sum::<u128>() - 151 + 1_000_000;
+		assert_eq!(tree.aggregate(100..200)?, expected);
+		assert!(tree.verify()?.is_ok());
+	}
+	remove_file("aggregate_sum")
+}
+
+#[test]
+fn sum_overflow() -> Result<()> {
+	let _ = remove_file("aggregate_sum_overflow");
+	let _ = remove_file("aggregate_sum_overflow_i8");
+	{
+		// The sum of `u16` values is computed in `u64`, So it doesn't overflow.
+		let mut tree = BPlusTree::<u32, u16, 128, Sum>::open("aggregate_sum_overflow")?;
+		for i in 0..1000u32 {
+			tree.set(i, u16::MAX, SetOption::UpdateOrInsert)?;
+		}
+		assert_eq!(tree.aggregate(..)?, 1000 * u16::MAX as u64);
+		tree.delete(&0)?;
+		assert_eq!(tree.aggregate(..)?, 999 * u16::MAX as u64);
+		assert!(tree.verify()?.is_ok());
+
+		let mut tree = BPlusTree::<u32, i8, 128, Sum>::open("aggregate_sum_overflow_i8")?;
+		for i in 0..1000u32 {
+			tree.set(i, i8::MIN, SetOption::UpdateOrInsert)?;
+		}
+		assert_eq!(tree.aggregate(..)?, 1000 * i8::MIN as i64);
+	}
+	remove_file("aggregate_sum_overflow")?;
+	remove_file("aggregate_sum_overflow_i8")
+}
+
+#[test]
+fn min_max_count() -> Result<()> {
+	let _ = remove_file("aggregate_min");
+	let _ = remove_file("aggregate_max");
+	let _ = remove_file("aggregate_count");
+	{
+		let mut min = BPlusTree::<u32, i32, 128, Min>::open("aggregate_min")?;
+		let mut max = BPlusTree::<u32, i32, 128, Max>::open("aggregate_max")?;
+		let mut count = BPlusTree::<u32, i32, 128, Count>::open("aggregate_count")?;
+		assert_eq!(min.aggregate(..)?, None);
+		for i in 0..1000u32 {
+			let value = (i as i32 - 500) * (i as i32 % 7);
+			min.set(i, value, SetOption::UpdateOrInsert)?;
+			max.set(i, value, SetOption::UpdateOrInsert)?;
+			count.set(i, value, SetOption::UpdateOrInsert)?;
+		}
+		let values = || (10..900).map(|i| (i - 500) * (i % 7));
+		assert_eq!(min.aggregate(10..900)?, values().min());
+		assert_eq!(max.aggregate(10..900)?, values().max());
+		assert_eq!(count.aggregate(10..900)?, 890);
+		assert!(min.verify()?.is_ok());
+	}
+	remove_file("aggregate_min")?;
+	remove_file("aggregate_max")?;
+	remove_file("aggregate_count")
+}
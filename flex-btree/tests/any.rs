@@ -0,0 +1,55 @@
+use std::{fs::remove_file, io::Result};
+
+use flex_btree::{convert_page_size, page_size, AnyTree, BPlusTree, Error, SetOption};
+
+#[test]
+fn runtime_page_size() -> Result<()> {
+	let _ = remove_file("runtime_page_size");
+	let _ = remove_file("runtime_page_size_1024");
+	{
+		let mut tree = BPlusTree::<u64, u16, 512>::open("runtime_page_size")?;
+		for i in 0..3000u64 {
+			let key = i * 7919 % 3000;
+			tree.set(key, key as u16, SetOption::UpdateOrInsert)?;
+		}
+	}
+	assert_eq!(page_size("runtime_page_size")?, 512);
+	{
+		let mut tree = AnyTree::<u64, u16>::open("runtime_page_size")?;
+		assert!(matches!(tree, AnyTree::P512(_)));
+		assert_eq!(tree.len(), 3000);
+		assert_eq!(tree.set(3000, 1, SetOption::UpdateOrInsert)?, None);
+		assert_eq!(tree.nth(1)?, Some((1, 1)));
+	}
+	assert!(AnyTree::<u64, u16>::create("runtime_page_size_1024", 1000).is_err());
+
+	convert_page_size::<u64, u16, ()>("runtime_page_size", "runtime_page_size_1024", 1024)?;
+	assert_eq!(page_size("runtime_page_size_1024")?, 1024);
+	{
+		let tree = BPlusTree::<u64, u16, 1024>::open("runtime_page_size_1024")?;
+		assert_eq!(tree.len(), 3001);
+		assert_eq!(tree.get_many(&[0, 1, 3000, 3001])?, [Some(0), Some(1), Some(1), None]);
+		let report = tree.verify()?;
+		assert!(report.is_ok(), "{:?}", report.problems);
+		// Wrong page size.
+		assert!(BPlusTree::<u64, u16, 512>::open("runtime_page_size_1024").is_err());
+	}
+	remove_file("runtime_page_size")?;
+	remove_file("runtime_page_size_1024")
+}
+
+#[test]
+fn unsupported_page_size() -> Result<()> {
+	let _ = remove_file("unsupported_page_size");
+	{
+		BPlusTree::<u64, u16, 64>::open("unsupported_page_size")?;
+	}
+	// The header has the page size, But `AnyTree` has no variant for it.
+	assert_eq!(page_size("unsupported_page_size")?, 64);
+	match AnyTree::<u64, u16>::open("unsupported_page_size") {
+		Err(Error::FormatMismatch { found, .. }) => assert_eq!(found, "64"),
+		_ => panic!("Expected a format mismatch"),
+	}
+	assert!(BPlusTree::<u64, u16, 64>::open("unsupported_page_size").is_ok());
+	remove_file("unsupported_page_size")
+}
@@ -1,3 +1,4 @@
+use flex_btree::Error;
 use std::{fs::remove_file, io::Result};
 
 // use flex_btree::SetOption;
@@ -10,18 +11,65 @@ fn open_file() -> Result<()> {
 	let _ = remove_file("open_file");
 	{
 		let _btree = BTree::open("open_file")?;
-		assert_eq!(
-			"The file is already opened.",
-			BTree::open("open_file").err().unwrap().to_string()
-		);
+		assert!(matches!(BTree::open("open_file"), Err(Error::AlreadyOpen)));
+	}
+	match flex_btree::BPlusTree::<u32, u32, 128>::open("open_file") {
+		Err(Error::FormatMismatch { expected, found }) => {
+			assert_eq!(
+				expected,
+				"MetaInfo { version: 1, key_size: 4, value_size: 4, aggregate_size: 0, block_size: 128 }"
+			);
+			assert_eq!(
+				found,
+				"MetaInfo { version: 1, key_size: 8, value_size: 2, aggregate_size: 0, block_size: 64 }"
+			);
+		}
+		_ => panic!("Expected a format mismatch"),
 	}
-	assert_eq!(
-		"Expected: MetaInfo { key_size: 8, value_size: 2, block_size: 64 }, but got: MetaInfo { key_size: 4, value_size: 4, block_size: 128 }",
-		flex_btree::BPlusTree::<u32, u32, 128>::open("open_file")
-			.err()
-			.unwrap()
-			.to_string()
-	);
 	assert!(BTree::open("open_file").err().is_none());
 	remove_file("open_file")
 }
+
+#[test]
+fn old_format() -> Result<()> {
+	// Metadata page of a file, That is written before the format has a version:
+	// key_size (1) + value_size (1) + block_size (4) + is_opened (1) + len (4) + root (2)
+	let mut bytes = vec![0; 2 * 64];
+	bytes[..13].copy_from_slice(&[8, 2, 64, 0, 0, 0, 0, 0, 0, 0, 0, 1, 0]);
+	std::fs::write("old_format", bytes)?;
+
+	match BTree::open("old_format") {
+		Err(Error::FormatMismatch { expected, found }) => {
+			assert_eq!(expected, "format version 1");
+			assert_eq!(found, "a file without format version");
+		}
+		_ => panic!("Expected a format mismatch"),
+	}
+	assert!(flex_btree::page_size("old_format").is_err());
+	remove_file("old_format")
+}
+
+#[test]
+fn invalid_key() -> Result<()> {
+	let _ = remove_file("invalid_key");
+	{
+		let mut btree = flex_btree::BPlusTree::<f64, u16, 64>::open("invalid_key")?;
+		let set = btree.set(f64::NAN, 1, flex_btree::SetOption::UpdateOrInsert);
+		assert!(matches!(set, Err(Error::InvalidKey)));
+		assert!(matches!(btree.delete(&f64::NAN), Err(Error::InvalidKey)));
+		btree.set(1.0, 1, flex_btree::SetOption::UpdateOrInsert)?;
+		let get = btree.get(flex_btree::Get::Exact(f64::NAN));
+		assert!(matches!(get, Err(Error::InvalidKey)));
+		assert_eq!(btree.get(flex_btree::Get::First)?.find(&f64::NAN), None);
+		assert!(matches!(btree.rank(&f64::NAN), Err(Error::InvalidKey)));
+		assert!(matches!(btree.count_range(f64::NAN..), Err(Error::InvalidKey)));
+		assert!(matches!(btree.aggregate(..=f64::NAN), Err(Error::InvalidKey)));
+		assert!(matches!(btree.delete_range(0.0..f64::NAN), Err(Error::InvalidKey)));
+		let cas = btree.compare_and_swap(f64::NAN, None, Some(1));
+		assert!(matches!(cas, Err(Error::InvalidKey)));
+		assert_eq!(btree.len(), 1);
+	}
+	let open = flex_btree::BPlusTree::<[u8; 32], u64, 64>::open("invalid_key");
+	assert!(matches!(open, Err(Error::KeyTooLarge)));
+	remove_file("invalid_key")
+}
@@ -0,0 +1,38 @@
+use std::{fs::remove_file, io::Result};
+
+use flex_btree::{Error, SetOption};
+
+type BTree = flex_btree::BPlusTree<u64, u16, 64>;
+
+#[test]
+fn get_many() -> Result<()> {
+	let _ = remove_file("get_many");
+	{
+		let mut tree = BTree::open("get_many")?;
+		assert_eq!(tree.get_many(&[1, 2])?, [None, None]);
+		for i in 0..1000u64 {
+			tree.set(i * 2, i as u16, SetOption::UpdateOrInsert)?;
+		}
+		assert_eq!(tree.get_many(&[])?, []);
+
+		let keys: Vec<u64> = (0..500).map(|i| i * 7919 % 2100).chain([4, 4, 5]).collect();
+		let values = tree.get_many(&keys)?;
+		for (key, value) in keys.iter().zip(values) {
+			let expected = if key % 2 == 0 && *key < 2000 { Some((key / 2) as u16) } else { None };
+			assert_eq!(value, expected, "key: {}", key);
+		}
+	}
+	remove_file("get_many")
+}
+
+#[test]
+fn get_many_nan() -> Result<()> {
+	let _ = remove_file("get_many_nan");
+	{
+		let mut tree = flex_btree::BPlusTree::<f64, u16, 64>::open("get_many_nan")?;
+		tree.set(1.0, 1, SetOption::UpdateOrInsert)?;
+		assert!(matches!(tree.get_many(&[1.0, f64::NAN, 2.0]), Err(Error::InvalidKey)));
+		assert_eq!(tree.get_many(&[2.0, 1.0])?, [None, Some(1)]);
+	}
+	remove_file("get_many_nan")
+}
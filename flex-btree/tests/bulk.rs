@@ -0,0 +1,67 @@
+use std::{fs::remove_file, io::Result};
+
+use flex_btree::{Get, SetOption};
+
+type BTree = flex_btree::BPlusTree<u64, u16, 64>;
+
+fn keys(tree: &BTree) -> Result<Vec<u64>> {
+	let mut keys = Vec::new();
+	let mut view = tree.get(Get::First)?;
+	loop {
+		keys.extend(view.iter().map(|(k, _)| *k));
+		if !view.next()? {
+			return Ok(keys);
+		}
+	}
+}
+
+#[test]
+fn delete_range() -> Result<()> {
+	let _ = remove_file("delete_range");
+	{
+		let mut tree = BTree::open("delete_range")?;
+		for i in 0..2000u64 {
+			tree.set(i * 7919 % 2000, i as u16, SetOption::UpdateOrInsert)?;
+		}
+		let free_pages = tree.stats()?.free_pages;
+
+		assert_eq!(tree.delete_range(500..1500)?, 1000);
+		assert_eq!(tree.delete_range(500..1500)?, 0);
+		assert_eq!(tree.delete_range(..=9)?, 10);
+		assert_eq!(tree.delete_range(1990..)?, 10);
+		assert_eq!(tree.delete_range(100..=100)?, 1);
+		assert_eq!(tree.len(), 979);
+		// Pages of the dropped subtrees are reused.
+		assert!(tree.stats()?.free_pages > free_pages);
+
+		let report = tree.verify()?;
+		assert!(report.is_ok(), "{:?}", report.problems);
+		let expected: Vec<_> = (10..500).chain(1500..1990).filter(|&k| k != 100).collect();
+		assert_eq!(keys(&tree)?, expected);
+
+		assert_eq!(tree.delete_range(..)?, 979);
+		assert_eq!(tree.len(), 0);
+	}
+	remove_file("delete_range")
+}
+
+#[test]
+fn retain() -> Result<()> {
+	let _ = remove_file("retain");
+	{
+		let mut tree = BTree::open("retain")?;
+		for i in 0..2000u64 {
+			tree.set(i, i as u16, SetOption::UpdateOrInsert)?;
+		}
+		assert_eq!(tree.retain(|k, _| k % 10 == 0 || (300..400).contains(k))?, 1710);
+		assert_eq!(tree.len(), 290);
+		let report = tree.verify()?;
+		assert!(report.is_ok(), "{:?}", report.problems);
+		assert_eq!(keys(&tree)?.len(), 290);
+
+		assert_eq!(tree.retain(|_, _| false)?, 290);
+		assert_eq!(tree.len(), 0);
+		assert!(tree.verify()?.is_ok());
+	}
+	remove_file("retain")
+}
@@ -0,0 +1,41 @@
+use std::{fs::remove_file, io::Result};
+
+use flex_btree::{Current, Get, Outcome};
+
+type BTree = flex_btree::BPlusTree<u64, u16, 64>;
+
+#[test]
+fn compare_and_swap() -> Result<()> {
+	let _ = remove_file("compare_and_swap");
+	let mut tree = BTree::open("compare_and_swap")?;
+
+	// Acquire, Renew and release a lease.
+	assert_eq!(tree.compare_and_swap(1, None, Some(10))?, Ok(()));
+	assert_eq!(tree.compare_and_swap(1, None, Some(20))?, Err(Current(Some(10))));
+	assert_eq!(tree.compare_and_swap(1, Some(10), Some(11))?, Ok(()));
+	assert_eq!(tree.compare_and_swap(1, Some(10), None)?, Err(Current(Some(11))));
+	assert_eq!(tree.compare_and_swap(1, Some(11), None)?, Ok(()));
+	assert_eq!(tree.compare_and_swap(1, Some(11), None)?, Err(Current(None)));
+	assert_eq!(tree.len(), 0);
+
+	for i in 0..500u64 {
+		assert_eq!(tree.insert_if_absent(i, i as u16)?, Outcome::Inserted);
+	}
+	assert_eq!(tree.insert_if_absent(7, 0)?, Outcome::Exists(7));
+	assert_eq!(tree.update_if_present(7, 70)?, Outcome::Updated(7));
+	assert_eq!(tree.update_if_present(500, 0)?, Outcome::Missing);
+	assert_eq!(tree.len(), 500);
+	assert_eq!(tree.get(Get::Exact(7))?.find(&7), Some((7, 70)));
+	assert_eq!(tree.get(Get::Exact(500))?.find(&500), None);
+
+	for i in 0..500u64 {
+		let old = if i == 7 { 70 } else { i as u16 };
+		assert_eq!(tree.compare_and_swap(i, Some(old), None)?, Ok(()));
+	}
+	assert_eq!(tree.len(), 0);
+	let report = tree.verify()?;
+	assert!(report.is_ok(), "{:?}", report.problems);
+
+	drop(tree);
+	remove_file("compare_and_swap")
+}
@@ -0,0 +1,42 @@
+use std::{fs::remove_file, io::Result};
+
+use flex_btree::{Get, SetOption};
+
+type BTree = flex_btree::BPlusTree<u64, u16, 64>;
+
+#[test]
+fn delete() -> Result<()> {
+	let _ = remove_file("delete");
+	{
+		let mut tree = BTree::open("delete")?;
+		for i in 0..1000u64 {
+			tree.set(i, i as u16, SetOption::UpdateOrInsert)?;
+		}
+		let file_size = tree.stats()?.file_size;
+
+		assert_eq!(tree.delete(&1000)?, None);
+		for i in 0..1000u64 {
+			let key = i * 7919 % 1000;
+			assert_eq!(tree.delete(&key)?, Some((key, key as u16)));
+			if i % 97 == 0 {
+				let report = tree.verify()?;
+				assert!(report.is_ok(), "{:?}", report.problems);
+			}
+		}
+		assert_eq!(tree.len(), 0);
+		assert!(tree.get(Get::First)?.is_empty());
+
+		let stats = tree.stats()?;
+		assert_eq!(stats.height, 1);
+		assert!(stats.free_pages > 0);
+		assert!(tree.verify()?.is_ok());
+
+		// Freed pages are reused.
+		for i in 0..1000u64 {
+			tree.set(i, i as u16, SetOption::UpdateOrInsert)?;
+		}
+		assert_eq!(tree.stats()?.file_size, file_size);
+		assert!(tree.verify()?.is_ok());
+	}
+	remove_file("delete")
+}
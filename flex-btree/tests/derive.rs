@@ -0,0 +1,71 @@
+use flex_btree::{Key, SetOption};
+use std::{fs::remove_file, io::Result};
+
+#[derive(Key, Debug, Clone, Copy)]
+struct Point {
+	x: i32,
+	y: u8,
+}
+
+#[derive(Key, Debug, Clone, Copy)]
+enum Shape {
+	Empty,
+	Circle(u16),
+	Rect { at: Point, size: u64 },
+}
+
+#[test]
+fn derive_key() {
+	assert_eq!(Point::SIZE, 5);
+	assert_eq!(Shape::SIZE, 1 + 5 + 8);
+
+	let shapes = [
+		Shape::Empty,
+		Shape::Circle(3),
+		Shape::Rect {
+			at: Point { x: -1, y: 2 },
+			size: 9,
+		},
+	];
+	for shape in shapes {
+		let bytes = shape.to_bytes();
+		assert_eq!(bytes.len(), Shape::SIZE);
+		assert_eq!(Shape::from_bytes(&bytes), shape);
+	}
+	// Fields are compared in declaration order, And variants by their declaration.
+	assert!(Point { x: 1, y: 0 } > Point { x: 0, y: 9 });
+	assert!(Point { x: 1, y: 0 } < Point { x: 1, y: 1 });
+	assert!(Shape::Empty < Shape::Circle(0));
+	assert!(
+		Shape::Circle(7)
+			< Shape::Rect {
+				at: Point { x: 0, y: 0 },
+				size: 0
+			}
+	);
+}
+
+#[test]
+fn derive_key_tree() -> Result<()> {
+	let _ = remove_file("derive_key_tree");
+	{
+		let mut btree = flex_btree::BPlusTree::<Point, Shape, 128>::open("derive_key_tree")?;
+		for x in (0..500).rev() {
+			let point = Point {
+				x,
+				y: (x % 3) as u8,
+			};
+			assert!(btree
+				.set(point, Shape::Circle(x as u16), SetOption::UpdateOrInsert)?
+				.is_none());
+		}
+		let point = Point { x: 42, y: 0 };
+		assert_eq!(
+			btree.set(point, Shape::Empty, SetOption::UpdateOrInsert)?,
+			Some(Shape::Circle(42))
+		);
+		assert_eq!(btree.delete(&point)?, Some((point, Shape::Empty)));
+		assert_eq!(btree.delete(&Point { x: 42, y: 1 })?, None);
+	}
+	remove_file("derive_key_tree")
+}
@@ -0,0 +1,60 @@
+use std::{fs::remove_file, io::Result};
+
+use flex_btree::{Max, SetOption};
+
+type BTree = flex_btree::BPlusTree<u64, u16, 64>;
+
+#[test]
+fn dump() -> Result<()> {
+	let _ = remove_file("dump");
+	{
+		let mut tree = BTree::open("dump")?;
+		for i in 0..20 {
+			tree.set(i, i as u16, SetOption::UpdateOrInsert)?;
+		}
+		let stats = tree.stats()?;
+
+		let mut dot = Vec::new();
+		tree.dump_dot(&mut dot)?;
+		let dot = String::from_utf8(dot).unwrap();
+		assert!(dot.starts_with("digraph BPlusTree {"));
+		assert!(dot.trim_end().ends_with('}'));
+		let next_links = dot.matches("style=dashed").count() as u64;
+		assert_eq!(next_links, stats.leaf_pages - 1);
+
+		let mut json = Vec::new();
+		tree.dump_json(&mut json)?;
+		let json = String::from_utf8(json).unwrap();
+		assert!(json.starts_with("{\"root\":"));
+		assert!(json.contains("\"len\":20"));
+		assert!(json.contains("[\"19\",\"19\"]"));
+		let leaves = json.matches("\"type\":\"leaf\"").count() as u64;
+		assert_eq!(leaves, stats.leaf_pages);
+	}
+	remove_file("dump")
+}
+
+#[test]
+fn dump_counts() -> Result<()> {
+	let _ = remove_file("dump_counts");
+	{
+		let mut tree = flex_btree::BPlusTree::<u64, u16, 64, Max>::open("dump_counts")?;
+		for i in 0..20 {
+			tree.set(i, i as u16, SetOption::UpdateOrInsert)?;
+		}
+		let mut json = Vec::new();
+		tree.dump_json(&mut json)?;
+		let json = String::from_utf8(json).unwrap();
+
+		// The root is dumped first.
+		let list = |name: &str| {
+			let start = json.find(&format!("\"{}\":[", name)).unwrap() + name.len() + 4;
+			let len = json[start..].find(']').unwrap();
+			json[start..start + len].split(',').map(str::to_string).collect::<Vec<_>>()
+		};
+		let counts: u32 = list("counts").iter().map(|c| c.parse::<u32>().unwrap()).sum();
+		assert_eq!(counts, 20);
+		assert_eq!(list("aggs").last().unwrap(), "\"Some(19)\"");
+	}
+	remove_file("dump_counts")
+}
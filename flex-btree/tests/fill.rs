@@ -0,0 +1,88 @@
+use std::{fs::remove_file, io::Result};
+
+use flex_btree::SetOption;
+
+type BTree = flex_btree::BPlusTree<u64, u16, 256>;
+
+#[test]
+fn fill_factor() -> Result<()> {
+	let _ = remove_file("fill_factor");
+	{
+		let mut tree = BTree::open("fill_factor")?;
+		assert_eq!(tree.fill_factor(), flex_btree::DEFAULT_FILL_FACTOR);
+		assert!(tree.set_fill_factor(20).is_err());
+		for i in 0..5000u64 {
+			tree.set(i, i as u16, SetOption::UpdateOrInsert)?;
+		}
+		// Sequential appends are splitted at the fill factor.
+		let stats = tree.stats()?;
+		assert!(stats.avg_fill > 0.85, "{}", stats.avg_fill);
+		let report = tree.verify()?;
+		assert!(report.is_ok(), "{:?}", report.problems);
+
+		tree.clear()?;
+		tree.set_fill_factor(50)?;
+		for i in 0..5000u64 {
+			tree.set(i, i as u16, SetOption::UpdateOrInsert)?;
+		}
+		let stats = tree.stats()?;
+		assert!(stats.avg_fill < 0.6, "{}", stats.avg_fill);
+	}
+	{
+		let mut tree = BTree::open("fill_factor")?;
+		assert_eq!(tree.fill_factor(), 50);
+		// Appends by `merge` are also splitted at the fill factor.
+		tree.clear()?;
+		tree.set_fill_factor(95)?;
+		tree.set_merge_operator(|old, n| Some(old.unwrap_or(0) + n));
+		for i in 0..5000u64 {
+			tree.merge(i, 1)?;
+		}
+		let stats = tree.stats()?;
+		assert!(stats.avg_fill > 0.85, "{}", stats.avg_fill);
+		let report = tree.verify()?;
+		assert!(report.is_ok(), "{:?}", report.problems);
+	}
+	{
+		let tree = BTree::open("fill_factor")?;
+		assert_eq!(tree.fill_factor(), 95);
+	}
+	remove_file("fill_factor")
+}
+
+#[test]
+fn full_fill_factor() -> Result<()> {
+	let _ = remove_file("full_fill_factor");
+	for seed in 0..16u64 {
+		let mut tree = BTree::open("full_fill_factor")?;
+		tree.clear()?;
+		tree.set_fill_factor(100)?;
+		let mut keys = std::collections::BTreeSet::new();
+		let mut rng = seed;
+		let mut next = || {
+			rng = rng.wrapping_mul(6364136223846793005).wrapping_add(1442695040888963407);
+			rng >> 33
+		};
+		for i in 0..2000u64 {
+			// Mostly appends, So the right most nodes are splitted at the fill factor.
+			let key = if next() % 4 == 0 { next() % (i + 1) } else { i };
+			tree.set(key, key as u16, SetOption::UpdateOrInsert)?;
+			keys.insert(key);
+			if next() % 3 == 0 {
+				let last = keys.iter().next_back().copied();
+				keys.remove(&last.unwrap());
+				assert_eq!(tree.pop_last()?.map(|(k, _)| k), last);
+			}
+		}
+		while let Some(last) = keys.iter().next_back().copied() {
+			assert_eq!(tree.get(flex_btree::Get::Last)?.last().map(|(k, _)| *k), Some(last), "seed {}", seed);
+			keys.remove(&last);
+			assert_eq!(tree.pop_last()?.map(|(k, _)| k), Some(last));
+			if keys.len() % 500 == 0 {
+				let report = tree.verify()?;
+				assert!(report.is_ok(), "seed {}: {:?}", seed, report.problems);
+			}
+		}
+	}
+	remove_file("full_fill_factor")
+}
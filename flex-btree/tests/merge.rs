@@ -0,0 +1,43 @@
+use std::{fs::remove_file, io::Result};
+
+use flex_btree::{Get, SetOption, Sum};
+
+type BTree = flex_btree::BPlusTree<u64, u64, 128, Sum>;
+
+#[test]
+fn merge() -> Result<()> {
+	let _ = remove_file("merge");
+	let mut tree = BTree::open("merge")?;
+	assert!(tree.merge(1, 1).is_err());
+
+	// Counter, That is deleted when it reaches zero.
+	tree.set_merge_operator(|old, n| match old.unwrap_or(0) + n {
+		100 => None,
+		sum => Some(sum),
+	});
+	for i in 0..3000u64 {
+		tree.merge(i % 500, 1)?;
+	}
+	assert_eq!(tree.len(), 500);
+	assert_eq!(tree.get(Get::Exact(7))?.find(&7), Some((7, 6)));
+	assert_eq!(tree.aggregate(..)?, 3000);
+
+	for i in 0..500u64 {
+		if i % 2 == 0 {
+			assert_eq!(tree.merge(i, 94)?, None);
+		}
+	}
+	assert_eq!(tree.len(), 250);
+	assert_eq!(tree.merge(0, 100)?, None);
+	assert_eq!(tree.len(), 250);
+	assert_eq!(tree.aggregate(..)?, 1500);
+
+	let report = tree.verify()?;
+	assert!(report.is_ok(), "{:?}", report.problems);
+
+	tree.set(1, 10, SetOption::UpdateOrInsert)?;
+	assert_eq!(tree.merge(1, 5)?, Some(15));
+
+	drop(tree);
+	remove_file("merge")
+}
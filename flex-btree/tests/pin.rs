@@ -0,0 +1,44 @@
+use std::{collections::BTreeMap, fs::remove_file, io::Result};
+
+use flex_btree::{Get, SetOption};
+
+type BTree = flex_btree::BPlusTree<u64, u16, 128>;
+
+#[test]
+fn pinned_levels() -> Result<()> {
+	let _ = remove_file("pinned_levels");
+	let mut map = BTreeMap::new();
+	{
+		let mut tree = BTree::open("pinned_levels")?;
+		assert_eq!(tree.pinned_levels(), flex_btree::DEFAULT_PINNED_LEVELS);
+		tree.pin_levels(2)?;
+		// Grow the tree through the pinned levels, Then shrink it back.
+		for i in 0..3000u64 {
+			let key = i * 7919 % 3000;
+			tree.set(key, i as u16, SetOption::UpdateOrInsert)?;
+			map.insert(key, i as u16);
+		}
+		for key in (0..3000u64).filter(|k| k % 3 != 0) {
+			assert_eq!(tree.delete(&key)?.map(|(_, v)| v), map.remove(&key));
+		}
+		for (key, value) in &map {
+			assert_eq!(tree.get(Get::Exact(*key))?.find(key), Some((*key, *value)));
+		}
+		let report = tree.verify()?;
+		assert!(report.is_ok(), "{:?}", report.problems);
+
+		tree.pin_levels(0)?;
+		assert_eq!(tree.get_many(&[0, 1, 3])?, [map.get(&0).copied(), None, map.get(&3).copied()]);
+	}
+	{
+		// Every write went through to the file.
+		let mut tree = BTree::open("pinned_levels")?;
+		assert_eq!(tree.len() as usize, map.len());
+		let keys: Vec<_> = map.keys().copied().collect();
+		let values: Vec<_> = map.values().map(|&v| Some(v)).collect();
+		assert_eq!(tree.get_many(&keys)?, values);
+		tree.clear()?;
+		assert_eq!(tree.pop_first()?, None);
+	}
+	remove_file("pinned_levels")
+}
@@ -0,0 +1,39 @@
+use std::{fs::remove_file, io::Result};
+
+use flex_btree::SetOption;
+
+type BTree = flex_btree::BPlusTree<u64, u16, 64>;
+
+#[test]
+fn pop() -> Result<()> {
+	let _ = remove_file("pop");
+	let mut tree = BTree::open("pop")?;
+	assert_eq!(tree.pop_first()?, None);
+	assert_eq!(tree.pop_last()?, None);
+
+	for i in 0..1000u64 {
+		tree.set(i * 7919 % 1000, i as u16, SetOption::UpdateOrInsert)?;
+	}
+	// Only the jobs that are due.
+	for due in 0..100u64 {
+		assert_eq!(tree.pop_first_if(|k, _| *k < 100)?.map(|(k, _)| k), Some(due));
+	}
+	assert_eq!(tree.pop_first_if(|k, _| *k < 100)?, None);
+	assert_eq!(tree.len(), 900);
+
+	for i in 0..450u64 {
+		assert_eq!(tree.pop_first()?.map(|(k, _)| k), Some(100 + i));
+		assert_eq!(tree.pop_last()?.map(|(k, _)| k), Some(999 - i));
+		if i % 97 == 0 {
+			let report = tree.verify()?;
+			assert!(report.is_ok(), "{:?}", report.problems);
+		}
+	}
+	assert_eq!(tree.len(), 0);
+	assert_eq!(tree.pop_first()?, None);
+	let report = tree.verify()?;
+	assert!(report.is_ok(), "{:?}", report.problems);
+
+	drop(tree);
+	remove_file("pop")
+}
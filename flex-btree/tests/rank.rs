@@ -0,0 +1,44 @@
+use std::{fs::remove_file, io::Result, ops::Bound};
+
+use flex_btree::SetOption;
+
+type BTree = flex_btree::BPlusTree<u64, u16, 64>;
+
+#[test]
+fn rank() -> Result<()> {
+	let _ = remove_file("rank");
+	{
+		let mut tree = BTree::open("rank")?;
+		// Even keys: 0, 2, 4 .. 1998
+		for i in 0..1000u64 {
+			let key = i * 7919 % 1000 * 2;
+			tree.set(key, key as u16, SetOption::UpdateOrInsert)?;
+		}
+		for i in 0..1000 {
+			assert_eq!(tree.nth(i)?, Some((i as u64 * 2, i as u16 * 2)));
+		}
+		assert_eq!(tree.nth(1000)?, None);
+
+		assert_eq!(tree.rank(&0)?, 0);
+		assert_eq!(tree.rank(&11)?, 6);
+		assert_eq!(tree.rank(&12)?, 6);
+		assert_eq!(tree.rank(&5000)?, 1000);
+
+		assert_eq!(tree.count_range(..)?, 1000);
+		assert_eq!(tree.count_range(10..20)?, 5);
+		assert_eq!(tree.count_range(10..=20)?, 6);
+		assert_eq!(tree.count_range(11..)?, 994);
+		assert_eq!(tree.count_range((Bound::Excluded(20), Bound::Excluded(10)))?, 0);
+
+		// Counts are maintained by deletes.
+		for key in (0..1000).step_by(4) {
+			tree.delete(&key)?;
+		}
+		assert!(tree.verify()?.is_ok());
+		assert_eq!(tree.len(), 750);
+		assert_eq!(tree.nth(0)?, Some((2, 2)));
+		assert_eq!(tree.count_range(..1000)?, 250);
+		assert_eq!(tree.count_range(1000..)?, 500);
+	}
+	remove_file("rank")
+}
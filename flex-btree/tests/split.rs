@@ -0,0 +1,143 @@
+use std::{fs::remove_file, io::Result};
+
+use flex_btree::{Error, Get, SetOption};
+
+type BTree = flex_btree::BPlusTree<u64, u16, 64>;
+
+fn keys(tree: &BTree) -> Result<Vec<u64>> {
+	let mut keys = Vec::new();
+	let mut view = tree.get(Get::First)?;
+	loop {
+		keys.extend(view.iter().map(|(k, _)| *k));
+		if !view.next()? {
+			return Ok(keys);
+		}
+	}
+}
+
+#[test]
+fn split_off_and_append() -> Result<()> {
+	let _ = remove_file("split_off_left");
+	let _ = remove_file("split_off_right");
+	{
+		let mut left = BTree::open("split_off_left")?;
+		for i in 0..3000u64 {
+			left.set(i * 7919 % 3000, i as u16, SetOption::UpdateOrInsert)?;
+		}
+		let mut right = left.split_off(&1234, "split_off_right")?;
+		assert_eq!(left.len(), 1234);
+		assert_eq!(right.len(), 1766);
+		for tree in [&left, &right] {
+			let report = tree.verify()?;
+			assert!(report.is_ok(), "{:?}", report.problems);
+		}
+		assert_eq!(keys(&left)?, (0..1234).collect::<Vec<_>>());
+		assert_eq!(keys(&right)?, (1234..3000).collect::<Vec<_>>());
+		assert_eq!(right.get(Get::Exact(2000))?.find(&2000), Some((2000, 1000)));
+
+		// Keys must be greater.
+		assert!(right.append(&mut left).is_err());
+
+		left.append(&mut right)?;
+		assert_eq!(left.len(), 3000);
+		assert_eq!(right.len(), 0);
+		let report = left.verify()?;
+		assert!(report.is_ok(), "{:?}", report.problems);
+		assert_eq!(keys(&left)?, (0..3000).collect::<Vec<_>>());
+		assert_eq!(left.nth(2000)?, Some((2000, 1000)));
+	}
+	remove_file("split_off_left")?;
+	remove_file("split_off_right")
+}
+
+#[test]
+fn split_off_page_writes() -> Result<()> {
+	let _ = remove_file("split_off_page_writes_left");
+	let _ = remove_file("split_off_page_writes_right");
+	let _ = remove_file("split_off_page_writes_other");
+	{
+		let mut left = BTree::open("split_off_page_writes_left")?;
+		for i in 0..5000u64 {
+			left.set(i, i as u16, SetOption::UpdateOrInsert)?;
+		}
+		let height = left.stats()?.height as u64;
+
+		// Every moved page is written once into the new tree, Only the nodes on its left edge are written again
+		// (and their siblings, When they are rebalanced).
+		let writes = left.page_writes();
+		let right = left.split_off(&1234, "split_off_page_writes_right")?;
+		let stats = right.stats()?;
+		assert!(right.page_writes() <= stats.leaf_pages + stats.branch_pages + 5 * height, "{}", right.page_writes());
+		// This tree is cut along the path, And the moved pages are freed.
+		let freed = left.stats()?.free_pages;
+		assert!(left.page_writes() - writes <= freed + 4 * height, "{}", left.page_writes() - writes);
+
+		assert_eq!(right.len(), 3766);
+		assert_eq!(right.nth(0)?, Some((1234, 1234)));
+		for tree in [&left, &right] {
+			let report = tree.verify()?;
+			assert!(report.is_ok(), "{:?}", report.problems);
+		}
+		assert_eq!(keys(&right)?, (1234..5000).collect::<Vec<_>>());
+
+		// Attached as a whole.
+		let mut right = right;
+		let writes = left.page_writes();
+		let pages = right.stats()?;
+		left.append(&mut right)?;
+		assert!(left.page_writes() - writes <= pages.leaf_pages + pages.branch_pages + 4 * height);
+		assert_eq!(keys(&left)?, (0..5000).collect::<Vec<_>>());
+		let report = left.verify()?;
+		assert!(report.is_ok(), "{:?}", report.problems);
+
+		// A page size that is different, The entries are appended one by one.
+		let mut other = flex_btree::BPlusTree::<u64, u16, 128>::open("split_off_page_writes_other")?;
+		for i in 5000..6000u64 {
+			other.set(i, i as u16, SetOption::UpdateOrInsert)?;
+		}
+		left.append(&mut other)?;
+		assert_eq!(other.len(), 0);
+		assert_eq!(keys(&left)?, (0..6000).collect::<Vec<_>>());
+		assert_eq!(left.nth(5500)?, Some((5500, 5500)));
+		let report = left.verify()?;
+		assert!(report.is_ok(), "{:?}", report.problems);
+	}
+	remove_file("split_off_page_writes_left")?;
+	remove_file("split_off_page_writes_right")?;
+	remove_file("split_off_page_writes_other")
+}
+
+#[test]
+fn append_after_pop_last() -> Result<()> {
+	let _ = remove_file("append_after_pop_last");
+	let _ = remove_file("append_after_pop_last_other");
+	{
+		let mut tree = BTree::open("append_after_pop_last")?;
+		tree.set_fill_factor(100)?;
+		// `None` is a `pop_last`, This sequence used to leave the last leaf empty.
+		let (p, s) = (None, Some);
+		let ops = [s(0), s(1), s(2), s(3), s(0), p, s(5), p, s(6), s(7), s(4), s(2), p, s(10), s(11), s(12), s(13), s(14), s(15), p];
+		for op in ops {
+			match op {
+				Some(key) => drop(tree.set(key, key as u16, SetOption::UpdateOrInsert)?),
+				None => drop(tree.pop_last()?),
+			}
+		}
+		assert_eq!(tree.nth(tree.len() - 1)?, Some((14, 14)));
+
+		let mut other = BTree::open("append_after_pop_last_other")?;
+		for i in 14..20u64 {
+			other.set(i, i as u16, SetOption::UpdateOrInsert)?;
+		}
+		// `14` is still in this tree.
+		assert!(matches!(tree.append(&mut other), Err(Error::InvalidKey)));
+		assert_eq!(other.len(), 6);
+		assert_eq!(other.delete(&14)?, Some((14, 14)));
+		tree.append(&mut other)?;
+		assert_eq!(keys(&tree)?, [0, 1, 2, 4, 6, 10, 11, 12, 13, 14, 15, 16, 17, 18, 19]);
+		let report = tree.verify()?;
+		assert!(report.is_ok(), "{:?}", report.problems);
+	}
+	remove_file("append_after_pop_last")?;
+	remove_file("append_after_pop_last_other")
+}
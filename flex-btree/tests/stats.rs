@@ -0,0 +1,59 @@
+use std::{fs::remove_file, io::Result, os::unix::fs::FileExt};
+
+use flex_btree::SetOption;
+
+type BTree = flex_btree::BPlusTree<u64, u16, 64>;
+
+#[test]
+fn stats() -> Result<()> {
+	let _ = remove_file("stats");
+	{
+		let mut tree = BTree::open("stats")?;
+		let stats = tree.stats()?;
+		assert_eq!(stats.height, 1);
+		assert_eq!(stats.leaf_pages, 1);
+		assert_eq!(stats.file_size, 2 * 64);
+
+		for i in 0..1000 {
+			tree.set(i, i as u16, SetOption::UpdateOrInsert)?;
+		}
+		let stats = tree.stats()?;
+		assert!(stats.height > 1);
+		assert_eq!(stats.levels.len(), stats.height);
+		assert_eq!(stats.levels.last().unwrap().entries, 1000);
+		assert_eq!(stats.levels.last().unwrap().pages, stats.leaf_pages);
+		assert_eq!(stats.levels[0].pages, 1);
+		assert_eq!(stats.free_pages, 0);
+		assert_eq!(
+			stats.file_size,
+			(1 + stats.leaf_pages + stats.branch_pages) * 64
+		);
+		assert!(stats.min_fill > 0.0 && stats.min_fill <= stats.avg_fill);
+		assert!(stats.leaf_distance >= 1.0);
+	}
+	remove_file("stats")
+}
+
+#[test]
+fn free_list_cycle() -> Result<()> {
+	let _ = remove_file("free_list_cycle");
+	{
+		let mut tree = BTree::open("free_list_cycle")?;
+		for i in 0..1000 {
+			tree.set(i, i as u16, SetOption::UpdateOrInsert)?;
+		}
+		tree.delete_range(..900)?;
+		assert!(tree.stats()?.free_pages > 0);
+	}
+	// Every free page points to itself.
+	let file = std::fs::OpenOptions::new().read(true).write(true).open("free_list_cycle")?;
+	for page in 1..file.metadata()?.len() / 64 {
+		let mut node_type = [0];
+		file.read_exact_at(&mut node_type, page * 64)?;
+		if node_type[0] == 2 {
+			file.write_all_at(&(page as u16).to_le_bytes(), page * 64 + 1)?;
+		}
+	}
+	assert!(BTree::open("free_list_cycle")?.stats().is_err());
+	remove_file("free_list_cycle")
+}
@@ -0,0 +1,56 @@
+use std::{fs::remove_file, io::Result, time::Duration};
+
+use flex_btree::{Get, SetOption};
+
+type TtlTree = flex_btree::TtlTree<u64, u16, 64>;
+
+#[test]
+fn ttl() -> Result<()> {
+	let _ = remove_file("ttl");
+	let _ = remove_file("ttl.ttl");
+	{
+		let mut tree = TtlTree::open("ttl")?;
+		for i in 0..300u64 {
+			if i % 3 == 0 {
+				tree.set_with_ttl(i, i as u16, Duration::ZERO)?;
+			} else if i % 3 == 1 {
+				tree.set_with_ttl(i, i as u16, Duration::from_secs(3600))?;
+			} else {
+				tree.set(i, i as u16, SetOption::UpdateOrInsert)?;
+			}
+		}
+		assert_eq!(tree.len(), 300);
+
+		// Expired entries are hidden.
+		assert_eq!(tree.get(Get::Exact(3))?.find(&3), None);
+		assert_eq!(tree.get(Get::Exact(4))?.find(&4), Some(4));
+		assert_eq!(tree.get(Get::Exact(5))?.find(&5), Some(5));
+		assert!(tree.get(Get::First)?.iter().all(|(k, _)| k % 3 != 0));
+
+		// An expired entry is treated as absent.
+		assert_eq!(tree.set(6, 60, SetOption::FindOrInsert)?, None);
+		assert_eq!(tree.set(7, 70, SetOption::FindOrInsert)?, Some(7));
+		assert_eq!(tree.get(Get::Exact(6))?.find(&6), Some(60));
+		assert_eq!(tree.delete(&9)?, None);
+		assert_eq!(tree.delete(&10)?, Some((10, 10)));
+
+		// Renewed entry must not be reclaimed.
+		tree.set_with_ttl(12, 120, Duration::from_secs(3600))?;
+		// A huge ttl saturates, Instead of wrapping to an expired deadline.
+		tree.set_with_ttl(300, 300, Duration::MAX)?;
+		assert_eq!(tree.get(Get::Exact(300))?.find(&300), Some(300));
+
+		assert_eq!(tree.expire()?, 97);
+		assert_eq!(tree.len(), 202);
+		assert_eq!(tree.expire()?, 0);
+		assert_eq!(tree.get(Get::Exact(12))?.find(&12), Some(120));
+	}
+	{
+		let mut tree = TtlTree::open("ttl")?;
+		assert_eq!(tree.len(), 202);
+		assert_eq!(tree.get(Get::Exact(4))?.find(&4), Some(4));
+		assert_eq!(tree.expire()?, 0);
+	}
+	remove_file("ttl")?;
+	remove_file("ttl.ttl")
+}
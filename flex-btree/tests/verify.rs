@@ -0,0 +1,34 @@
+use std::{fs::remove_file, io::Result, os::unix::fs::FileExt};
+
+use flex_btree::{verify, Problem, SetOption};
+
+type BTree = flex_btree::BPlusTree<u64, u16, 64>;
+
+#[test]
+fn verify_tree() -> Result<()> {
+	let _ = remove_file("verify_tree");
+	{
+		let mut tree = BTree::open("verify_tree")?;
+		for i in 0..1000u64 {
+			tree.set(i * 7919 % 1000, i as u16, SetOption::UpdateOrInsert)?;
+		}
+		let report = tree.verify()?;
+		assert!(report.is_ok(), "{:?}", report.problems);
+	}
+	assert!(verify::<u64, u16, 64, ()>("verify_tree")?.is_ok());
+
+	// Corrupt the node type of the last page.
+	let file = std::fs::OpenOptions::new().write(true).open("verify_tree")?;
+	let last = file.metadata()?.len() - 64;
+	file.write_all_at(&[9], last)?;
+
+	let report = verify::<u64, u16, 64, ()>("verify_tree")?;
+	let page = (last / 64) as u16;
+	assert!(report.problems.contains(&Problem::Corrupted { page }));
+	assert!(report
+		.problems
+		.iter()
+		.any(|p| matches!(p, Problem::LenMismatch { expected: 1000, .. })));
+
+	remove_file("verify_tree")
+}
@@ -0,0 +1,39 @@
+use std::{fs::remove_file, io::Result};
+
+use flex_btree::{Event, SetOption};
+
+type BTree = flex_btree::BPlusTree<u64, u16, 64>;
+
+#[test]
+fn watch() -> Result<()> {
+	let _ = remove_file("watch");
+	let mut tree = BTree::open("watch")?;
+	let all = tree.watch(..);
+	let some = tree.watch(10..20);
+
+	for i in 0..100u64 {
+		tree.set(i, i as u16, SetOption::UpdateOrInsert)?;
+	}
+	tree.set(15, 150, SetOption::UpdateOrInsert)?;
+	// Nothing is changed.
+	tree.set(16, 160, SetOption::FindOrInsert)?;
+	tree.delete(&17)?;
+	tree.delete(&1000)?;
+
+	let events: Vec<_> = some.try_iter().collect();
+	assert_eq!(events.len(), 12);
+	assert_eq!(events[0], Event::Inserted { key: 10, new: 10 });
+	assert_eq!(events[10], Event::Updated { key: 15, old: 15, new: 150 });
+	assert_eq!(events[11], Event::Deleted { key: 17, old: 17 });
+	assert_eq!(all.try_iter().count(), 102);
+
+	// Dropped watchers are removed.
+	drop(all);
+	tree.clear()?;
+	let events: Vec<_> = some.try_iter().collect();
+	assert_eq!(events.len(), 9);
+	assert!(events.iter().all(|e| matches!(e, Event::Deleted { .. })));
+
+	drop(tree);
+	remove_file("watch")
+}
@@ -0,0 +1,275 @@
+//! Derive macros for the `Key` and `Value` traits of the index crates.
+//!
+//! The index crates re-export them under the name of their trait, So `#[derive(Key)]`
+//! works after `use flex_btree::Key` or `use flex_bptree::Key`.
+//!
+//! `Key` also implements `PartialEq` and `PartialOrd`, Which compare the fields in declaration
+//! order (and the variants of an enum in declaration order), So don't derive them as well.
+
+use proc_macro::TokenStream;
+use proc_macro2::TokenStream as TokenStream2;
+use quote::{format_ident, quote};
+use syn::{
+    parse_macro_input, parse_quote, Data, DeriveInput, Error, Fields, Generics, Ident, Member,
+    Path, Result, Type,
+};
+
+/// Implements `flex_btree::Key`.
+///
+/// A struct is encoded as its fields back to back. An enum is encoded as a tag (1) followed by
+/// the fields of the variant, Padded to the size of the largest variant.
+#[proc_macro_derive(BTreeKey)]
+pub fn btree_key(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+    let trait_path = parse_quote!(::flex_btree::Key);
+    expand(btree_key_impl(&input, &trait_path).and_then(|key| {
+        let ord = ord_impls(&input)?;
+        Ok(quote!(#key #ord))
+    }))
+}
+
+/// Implements `flex_bptree::Key`.
+///
+/// The encoded size comes from `bin_layout::Encoder`, Which must be derived as well.
+#[proc_macro_derive(BPlusTreeKey)]
+pub fn bplus_tree_key(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+    let key = marker_impl(&input, &parse_quote!(::flex_bptree::Key));
+    expand(ord_impls(&input).map(|ord| quote!(#key #ord)))
+}
+
+/// Implements `flex_bptree::Value`.
+#[proc_macro_derive(BPlusTreeValue)]
+pub fn bplus_tree_value(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+    marker_impl(&input, &parse_quote!(::flex_bptree::Value)).into()
+}
+
+fn expand(result: Result<TokenStream2>) -> TokenStream {
+    result.unwrap_or_else(Error::into_compile_error).into()
+}
+
+/// A struct or an enum variant.
+struct Variant {
+    path: TokenStream2,
+    members: Vec<Member>,
+    types: Vec<Type>,
+}
+
+impl Variant {
+    fn new(path: TokenStream2, fields: &Fields) -> Self {
+        let members = fields.members().collect();
+        let types = fields.iter().map(|field| field.ty.clone()).collect();
+        Self {
+            path,
+            members,
+            types,
+        }
+    }
+
+    /// Binds every field to `{prefix}{index}`, Braces also works for tuple and unit structs.
+    fn pattern(&self, prefix: &str) -> (TokenStream2, Vec<Ident>) {
+        let Self { path, members, .. } = self;
+        let binds: Vec<_> = (0..members.len())
+            .map(|i| format_ident!("{}{}", prefix, i))
+            .collect();
+        (quote!(#path { #(#members: #binds),* }), binds)
+    }
+}
+
+fn variants(input: &DeriveInput) -> Result<Vec<Variant>> {
+    match &input.data {
+        Data::Struct(data) => Ok(vec![Variant::new(quote!(Self), &data.fields)]),
+        Data::Enum(data) if data.variants.is_empty() => Err(Error::new_spanned(
+            input,
+            "`Key` can't be derived for an enum without variants",
+        )),
+        Data::Enum(data) if data.variants.len() > 256 => Err(Error::new_spanned(
+            input,
+            "`Key` can't be derived for an enum with more than 256 variants",
+        )),
+        Data::Enum(data) => Ok(data
+            .variants
+            .iter()
+            .map(|v| {
+                let ident = &v.ident;
+                Variant::new(quote!(Self::#ident), &v.fields)
+            })
+            .collect()),
+        Data::Union(_) => Err(Error::new_spanned(
+            input,
+            "`Key` can't be derived for a union",
+        )),
+    }
+}
+
+/// Adds `T: #bound` for every type parameter.
+fn bounded(generics: &Generics, bound: &Path) -> Generics {
+    let mut generics = generics.clone();
+    let params: Vec<_> = generics.type_params().map(|p| p.ident.clone()).collect();
+    let where_clause = generics.make_where_clause();
+    for param in params {
+        where_clause.predicates.push(parse_quote!(#param: #bound));
+    }
+    generics
+}
+
+fn marker_impl(input: &DeriveInput, trait_path: &Path) -> TokenStream2 {
+    let name = &input.ident;
+    let generics = bounded(&input.generics, trait_path);
+    let (impl_generics, ty_generics, where_clause) = generics.split_for_impl();
+    quote!(impl #impl_generics #trait_path for #name #ty_generics #where_clause {})
+}
+
+fn btree_key_impl(input: &DeriveInput, key: &Path) -> Result<TokenStream2> {
+    let variants = variants(input)?;
+    let name = &input.ident;
+    let generics = bounded(&input.generics, key);
+    let (impl_generics, ty_generics, where_clause) = generics.split_for_impl();
+
+    // Size of the fields, And the offset of every field from the start of the fields.
+    let size = |types: &[Type]| quote!(0 #(+ <#types as #key>::SIZE)*);
+    let offsets =
+        |types: &[Type]| -> Vec<_> { (0..types.len()).map(|i| size(&types[..i])).collect() };
+
+    let body = match &input.data {
+        Data::Struct(_) => {
+            let variant = &variants[0];
+            let types = &variant.types;
+            let offsets = offsets(types);
+            let (pattern, binds) = variant.pattern("__f");
+            let bytes = if types.is_empty() {
+                quote!(_)
+            } else {
+                quote!(bytes)
+            };
+            let total = size(types);
+            quote! {
+                const SIZE: usize = #total;
+                fn to_bytes(self) -> Vec<u8> {
+                    let mut bytes = Vec::with_capacity(<Self as #key>::SIZE);
+                    let #pattern = self;
+                    #(bytes.extend(#key::to_bytes(#binds));)*
+                    bytes
+                }
+                fn from_bytes(#bytes: &[u8]) -> Self {
+                    #(let #binds = <#types as #key>::from_bytes(
+                        &bytes[#offsets..#offsets + <#types as #key>::SIZE],
+                    );)*
+                    #pattern
+                }
+            }
+        }
+        _ => {
+            let sizes = variants.iter().map(|v| size(&v.types));
+            let tags = (0..variants.len()).map(|tag| tag as u8);
+            let (mut encode, mut decode) = (vec![], vec![]);
+            for (variant, tag) in variants.iter().zip(tags) {
+                let types = &variant.types;
+                let offsets = offsets(types);
+                let (pattern, binds) = variant.pattern("__f");
+                encode.push(quote! {
+                    #pattern => {
+                        bytes.push(#tag);
+                        #(bytes.extend(#key::to_bytes(#binds));)*
+                    }
+                });
+                decode.push(quote! {
+                    #tag => {
+                        #(let #binds = <#types as #key>::from_bytes(
+                            &bytes[1 + #offsets..1 + #offsets + <#types as #key>::SIZE],
+                        );)*
+                        #pattern
+                    }
+                });
+            }
+            quote! {
+                const SIZE: usize = {
+                    let mut max = 0;
+                    #(if #sizes > max { max = #sizes; })*
+                    1 + max
+                };
+                fn to_bytes(self) -> Vec<u8> {
+                    let mut bytes = Vec::with_capacity(<Self as #key>::SIZE);
+                    match self { #(#encode)* }
+                    bytes.resize(<Self as #key>::SIZE, 0);
+                    bytes
+                }
+                fn from_bytes(bytes: &[u8]) -> Self {
+                    match bytes[0] {
+                        #(#decode)*
+                        tag => panic!("invalid tag of `{}`: {}", stringify!(#name), tag),
+                    }
+                }
+            }
+        }
+    };
+    Ok(quote! {
+        impl #impl_generics #key for #name #ty_generics #where_clause {
+            #body
+        }
+    })
+}
+
+/// `PartialEq` and `PartialOrd`, In field order.
+fn ord_impls(input: &DeriveInput) -> Result<TokenStream2> {
+    let variants = variants(input)?;
+    let name = &input.ident;
+
+    let (mut eq_arms, mut cmp_arms, mut tag_arms) = (vec![], vec![], vec![]);
+    for (tag, variant) in variants.iter().enumerate() {
+        let (lhs, a) = variant.pattern("__a");
+        let (rhs, b) = variant.pattern("__b");
+        let path = &variant.path;
+        eq_arms.push(quote!((#lhs, #rhs) => true #(&& #a == #b)*,));
+        cmp_arms.push(quote! {
+            (#lhs, #rhs) => {
+                #(match ::core::cmp::PartialOrd::partial_cmp(#a, #b)? {
+                    ::core::cmp::Ordering::Equal => {}
+                    ord => return Some(ord),
+                })*
+                Some(::core::cmp::Ordering::Equal)
+            }
+        });
+        tag_arms.push(quote!(#path { .. } => #tag,));
+    }
+    // Different variants are ordered by their declaration.
+    let (ne_arm, tag_arm) = match variants.len() {
+        1 => (quote!(), quote!()),
+        _ => (
+            quote!(_ => false,),
+            quote! {
+                (a, b) => {
+                    let tag = |v: &Self| match v { #(#tag_arms)* };
+                    tag(a).partial_cmp(&tag(b))
+                }
+            },
+        ),
+    };
+
+    let eq_generics = bounded(&input.generics, &parse_quote!(::core::cmp::PartialEq));
+    let (impl_generics, ty_generics, where_clause) = eq_generics.split_for_impl();
+    let eq = quote! {
+        impl #impl_generics ::core::cmp::PartialEq for #name #ty_generics #where_clause {
+            fn eq(&self, other: &Self) -> bool {
+                match (self, other) {
+                    #(#eq_arms)*
+                    #ne_arm
+                }
+            }
+        }
+    };
+    let ord_generics = bounded(&input.generics, &parse_quote!(::core::cmp::PartialOrd));
+    let (impl_generics, ty_generics, where_clause) = ord_generics.split_for_impl();
+    Ok(quote! {
+        #eq
+        impl #impl_generics ::core::cmp::PartialOrd for #name #ty_generics #where_clause {
+            fn partial_cmp(&self, other: &Self) -> Option<::core::cmp::Ordering> {
+                match (self, other) {
+                    #(#cmp_arms)*
+                    #tag_arm
+                }
+            }
+        }
+    })
+}
@@ -0,0 +1,265 @@
+use super::*;
+use buffer::Buffer;
+
+impl<K: Key, V: Value, const SIZE: usize> RangeIdx<K, V, SIZE> {
+    /// #### _Blocking_
+    ///
+    /// Append the point `key`, It is stored as the interval `[key, key]`.
+    /// Keys must be appended in order, Every key must be greater than the keys (starts) of the index.
+    ///
+    /// Entries are buffered and the last leaf is written one full page at a time, Without searching for each key.
+    /// Buffered entries are visible to the queries, And they are written on [`RangeIdx::flush`] or on drop.
+    pub fn append(&mut self, key: K, value: V) -> Result<()> {
+        if key.partial_cmp(&key).is_none() {
+            return Err(Error::InvalidKey);
+        }
+        let key = (key.clone(), key);
+        if let Node::Leaf(leaf) = &self.root {
+            // The index fits in the root, So there is nothing to buffer.
+            if matches!(leaf.entries.last(), Some((last, _)) if key <= *last) {
+                return Err(Error::InvalidKey);
+            }
+            self.insert_entry(key, value)?;
+            return Ok(());
+        }
+        if self.buffer.is_none() {
+            self.buffer = Some(self.tail()?);
+        }
+        let buffer = self.buffer.as_mut().unwrap();
+        if matches!(&buffer.last, Some(last) if key <= *last) {
+            return Err(Error::InvalidKey);
+        }
+        buffer.last = Some(key.clone());
+        buffer.entries.push((key, value));
+        if buffer.is_full() {
+            self.flush()?;
+        }
+        Ok(())
+    }
+
+    /// #### _Blocking_
+    ///
+    /// Removes the point `key` of [`RangeIdx::append`], See [`RangeIdx::remove`].
+    pub fn remove_point(&mut self, key: K) -> Result<Option<V>> {
+        if key.partial_cmp(&key).is_none() {
+            return Err(Error::InvalidKey);
+        }
+        self.remove_entry((key.clone(), key))
+    }
+
+    /// #### _Blocking_
+    ///
+    /// Write the buffered entries of [`RangeIdx::append`].
+    pub fn flush(&mut self) -> Result<()> {
+        let (extend, entries) = match &mut self.buffer {
+            Some(buffer) if !buffer.entries.is_empty() => {
+                (buffer.extend, mem::take(&mut buffer.entries))
+            }
+            _ => return Ok(()),
+        };
+        let count = entries.len();
+        let mut root = mem::replace(&mut self.root, Node::Leaf(Leaf::new()));
+        let split = match self._flush(&mut root, extend, entries) {
+            Ok(split) => split,
+            Err(err) => {
+                self.root = root;
+                self.buffer = None;
+                return Err(err);
+            }
+        };
+        self.grow(root, split)?;
+        if let Some(buffer) = &mut self.buffer {
+            buffer.flushed(count);
+        }
+        Ok(())
+    }
+
+    /// Write the `entries` into the last leaf (if `extend` is `true`) or into a new leaf.
+    ///
+    /// Returns the split of the `node`, A full node is splitted before its last child,
+    /// So the left node stays full.
+    fn _flush(
+        &mut self,
+        node: &mut Node<K, V, SIZE>,
+        extend: bool,
+        entries: Vec<((K, K), V)>,
+    ) -> Result<Split<K>> {
+        let root = match node {
+            Node::Root(root) => root,
+            Node::Leaf(_) => unreachable!("Entries are buffered, Only if the root isn't a leaf"),
+        };
+        let last = root.childs.len() - 1;
+        let child_id = root.childs[last];
+        let mut child = self.read(child_id)?;
+        let split = match &mut child {
+            Node::Leaf(leaf) if extend => {
+                leaf.entries.extend(entries);
+                None
+            }
+            Node::Leaf(_) => {
+                let leaf = Leaf {
+                    entries: Record::new(entries),
+                };
+                let mid = leaf.entries[0].0.clone();
+                let max_end = leaf.max_end().unwrap();
+                Some((mid, self.create(Node::Leaf(leaf))?, max_end))
+            }
+            Node::Root(_) => self._flush(&mut child, extend, entries)?,
+        };
+        if let Some(max_end) = child.max_end() {
+            root.max_ends[last] = max_end;
+        }
+        // A new leaf doesn't change the last leaf.
+        if extend || matches!(child, Node::Root(_)) {
+            self.write(child_id, child)?;
+        }
+        if let Some((mid, right, right_max)) = split {
+            root.insert(last, mid, right, right_max);
+            if root.is_full() {
+                let (other, mid) = root.split_at(root.childs.len() - 1);
+                let other_max = other.max_end().unwrap();
+                let other_id = self.create(Node::Root(other))?;
+                return Ok(Some((mid, other_id, other_max)));
+            }
+        }
+        Ok(None)
+    }
+
+    /// Finds the last leaf, Appended entries are buffered for it.
+    fn tail(&self) -> Result<Buffer<K, V, SIZE>> {
+        let (mut last, mut child) = match &self.root {
+            Node::Root(root) => (
+                root.keys.last().cloned(),
+                root.childs[root.childs.len() - 1],
+            ),
+            Node::Leaf(_) => unreachable!("Entries are buffered, Only if the root isn't a leaf"),
+        };
+        let leaf = loop {
+            match self.read(child)? {
+                Node::Leaf(leaf) => break leaf,
+                Node::Root(root) => {
+                    // Every key of the index is less than the deepest separator key of the last child,
+                    // Except the keys of the last leaf.
+                    if let Some(key) = root.keys.last() {
+                        last = Some(key.clone());
+                    }
+                    child = root.childs[root.childs.len() - 1];
+                }
+            }
+        };
+        if let Some((key, _)) = leaf.entries.last() {
+            last = Some(key.clone());
+        }
+        let mut buffer = Buffer {
+            extend: true,
+            len: 0,
+            entries: vec![],
+            last,
+        };
+        buffer.flushed(leaf.entries.len());
+        Ok(buffer)
+    }
+
+    /// #### _Blocking_
+    ///
+    /// Entries whose start is in the time window `range`, Sorted by `(start, end)`.
+    pub fn scan(&self, range: Range<K>) -> Result<Vec<(K, V)>> {
+        let mut out = vec![];
+        self._scan(&self.root, &range, &mut out)?;
+        if let Some(buffer) = &self.buffer {
+            for ((start, _), value) in &buffer.entries {
+                if range.contains(start) {
+                    out.push((start.clone(), value.clone()));
+                }
+            }
+        }
+        Ok(out)
+    }
+
+    fn _scan(
+        &self,
+        node: &Node<K, V, SIZE>,
+        range: &Range<K>,
+        out: &mut Vec<(K, V)>,
+    ) -> Result<()> {
+        match node {
+            Node::Leaf(leaf) => {
+                for ((start, _), value) in leaf.entries.iter() {
+                    if *start >= range.end {
+                        break;
+                    }
+                    if *start >= range.start {
+                        out.push((start.clone(), value.clone()));
+                    }
+                }
+            }
+            Node::Root(root) => {
+                for (index, &child) in root.childs.iter().enumerate() {
+                    if index > 0 && root.keys[index - 1].0 >= range.end {
+                        break;
+                    }
+                    // Every interval of the child is less than the next separator key.
+                    if index < root.keys.len() && root.keys[index].0 < range.start {
+                        continue;
+                    }
+                    self._scan(&self.read(child)?, range, out)?;
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// #### _Blocking_
+    ///
+    /// Drop the leading leaves, Whose intervals all start before `key`.
+    ///
+    /// Only whole leaves are dropped, So some of the intervals before `key` may be kept.
+    /// The pages of the dropped nodes are reused.
+    pub fn drop_before(&mut self, key: K) -> Result<()> {
+        // So the buffered entries are in the last leaf.
+        self.flush()?;
+        // The index may shrink, So the last leaf is found again on the next append.
+        self.buffer = None;
+        let mut root = mem::replace(&mut self.root, Node::Leaf(Leaf::new()));
+        let ret = self._drop_before(&mut root, &key);
+        self.root = root;
+        ret?;
+        self.shrink()
+    }
+
+    fn _drop_before(&mut self, node: &mut Node<K, V, SIZE>, key: &K) -> Result<()> {
+        if let Node::Root(root) = node {
+            // Every interval of the child `i` is less than `keys[i]`, So the last child is never dropped.
+            let count = root
+                .keys
+                .iter()
+                .take_while(|(start, _)| start < key)
+                .count();
+            root.keys.drain(..count);
+            root.max_ends.drain(..count);
+            for child in root.childs.drain(..count).collect::<Vec<_>>() {
+                self.free_subtree(child)?;
+            }
+
+            let mut child = self.read(root.childs[0])?;
+            if let Node::Root(_) = child {
+                self._drop_before(&mut child, key)?;
+                if let Some(max_end) = child.max_end() {
+                    root.max_ends[0] = max_end;
+                }
+                self.write(root.childs[0], child)?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Pushes every page of the subtree to the free list.
+    fn free_subtree(&mut self, id: u16) -> Result<()> {
+        if let Node::Root(root) = self.read(id)? {
+            for child in root.childs {
+                self.free_subtree(child)?;
+            }
+        }
+        self.free(id)
+    }
+}
@@ -1,10 +1,29 @@
-use bin_layout::{Cursor, Decoder};
-use flex_page::Pages;
-use std::io;
+use crate::entry::{Key, Value};
+use crate::leaf::Leaf;
 
-// struct Buffer<'a, const SIZE: usize> {
-//     page: &'a Pages<SIZE>,
-//     data: Vec<u8>,
-// }
+/// Write buffer of the appended entries, It is flushed into the last leaf one full page at a time.
+pub struct Buffer<K, V, const SIZE: usize> {
+    /// The entries are flushed into the last leaf, Otherwise into a new leaf.
+    pub extend: bool,
+    /// Number of entries of the last leaf, That are on the disk.
+    pub len: usize,
+    pub entries: Vec<((K, K), V)>,
+    /// Largest key of the index, Appended keys must be greater.
+    pub last: Option<(K, K)>,
+}
 
+impl<K: Key, V: Value, const SIZE: usize> Buffer<K, V, SIZE> {
+    /// The last leaf is full, With the buffered entries.
+    pub fn is_full(&self) -> bool {
+        self.len + self.entries.len() >= Leaf::<K, V, SIZE>::capacity()
+    }
 
+    /// The buffered entries are written into the last leaf.
+    pub fn flushed(&mut self, count: usize) {
+        self.len += count;
+        self.extend = self.len < Leaf::<K, V, SIZE>::capacity();
+        if !self.extend {
+            self.len = 0;
+        }
+    }
+}
@@ -2,12 +2,12 @@ use bin_layout::{Decoder, Encoder};
 use std::fmt::Debug;
 
 pub trait Key:
-    Encoder + for<'de> Decoder<'de, ()> + PartialOrd + Send + Sync + Unpin + Debug
+    Encoder + for<'de> Decoder<'de, ()> + Clone + PartialOrd + Send + Sync + Unpin + Debug
 {
 }
 
 pub trait Value:
-    Encoder + for<'de> Decoder<'de, ()> + Send + Sync + Unpin + Debug
+    Encoder + for<'de> Decoder<'de, ()> + Clone + Send + Sync + Unpin + Debug
 {
 }
 
@@ -1,15 +1,16 @@
 use crate::entry::{Key, Value};
 use bin_layout::{Decoder, Encoder, Record};
 
+/// Entries are sorted by their interval `(start, end)`.
 #[derive(Encoder, Decoder)]
 pub struct Leaf<K, V, const SIZE: usize> {
-    pub entries: Record<u16, Vec<(K, V)>>,
+    pub entries: Record<u16, Vec<((K, K), V)>>,
 }
 
 impl<K: Key, V: Value, const SIZE: usize> Leaf<K, V, SIZE> {
     pub const fn capacity() -> usize {
         // BlockSize - (Node type (1) + entries len (2))
-        (SIZE - 3) / (K::SIZE + V::SIZE)
+        (SIZE - 3) / (2 * K::SIZE + V::SIZE)
     }
 
     pub fn new() -> Self {
@@ -17,4 +18,45 @@ impl<K: Key, V: Value, const SIZE: usize> Leaf<K, V, SIZE> {
             entries: Record::new(Vec::with_capacity(Self::capacity())),
         }
     }
+
+    pub fn is_full(&self) -> bool {
+        self.entries.len() >= Self::capacity()
+    }
+
+    pub fn binary_search(&self, key: &(K, K)) -> Result<usize, usize> {
+        self.entries
+            .binary_search_by(|(k, _)| k.partial_cmp(key).expect("Key can't be `NaN`"))
+    }
+
+    /// Returns the old value, If the interval was already present.
+    pub fn insert(&mut self, key: (K, K), value: V) -> Option<V> {
+        match self.binary_search(&key) {
+            Ok(index) => Some(std::mem::replace(&mut self.entries[index].1, value)),
+            Err(index) => {
+                self.entries.insert(index, (key, value));
+                None
+            }
+        }
+    }
+
+    pub fn remove(&mut self, key: &(K, K)) -> Option<V> {
+        let index = self.binary_search(key).ok()?;
+        Some(self.entries.remove(index).1)
+    }
+
+    /// Largest `end` of the intervals.
+    pub fn max_end(&self) -> Option<K> {
+        self.entries
+            .iter()
+            .map(|((_, end), _)| end.clone())
+            .reduce(crate::max)
+    }
+
+    /// This function splits `Self` at the middle and returns the right half.
+    pub fn split_at_mid(&mut self) -> Self {
+        let mid_point = self.entries.len() / 2;
+        Self {
+            entries: Record::new(self.entries.drain(mid_point..).collect()),
+        }
+    }
 }
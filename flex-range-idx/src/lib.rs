@@ -1,28 +1,144 @@
 #![allow(warnings)]
 
+mod append;
 mod buffer;
 mod entry;
 mod leaf;
 mod root;
 
-use bin_layout::{Cursor, Decoder};
+use bin_layout::{stack_array::ArrayBuf, Array, Cursor, Decoder, Encoder, Record};
+use buffer::Buffer;
 use flex_page::Pages;
 use leaf::Leaf;
 use root::Root;
-use std::{fs::File, io, path::Path};
+use std::{cmp::Ordering, fs::File, io, mem, ops::Range, path::Path};
+
+pub use entry::{Key, Value};
+pub use flex::{Error, Result};
+
+/// Separator key, Page number and max end of the new right node, When a node is splitted.
+type Split<K> = Option<((K, K), u16, K)>;
 
 enum Node<K, V, const SIZE: usize> {
     Leaf(Leaf<K, V, SIZE>),
     Root(Root<K, SIZE>),
 }
 
-struct RangeIdx<K, V, const SIZE: usize> {
+impl<K: Key, V: Value, const SIZE: usize> Node<K, V, SIZE> {
+    fn encode(self) -> [u8; SIZE] {
+        let mut arr = ArrayBuf::new();
+        match self {
+            Node::Leaf(leaf) => {
+                arr.push(0);
+                leaf.encoder(&mut arr);
+            }
+            Node::Root(root) => {
+                arr.push(1);
+                root.encoder(&mut arr);
+            }
+        }
+        to_page(arr)
+    }
+
+    /// `id` is the page number of the node, It is reported if the node is corrupted.
+    fn decoder(id: u16, buf: &[u8]) -> Result<Self> {
+        let corrupted = |()| Error::Corrupted { page: id.into() };
+        let mut c = Cursor::new(buf);
+        match u8::decoder(&mut c).map_err(corrupted)? {
+            0 => Ok(Node::Leaf(Leaf::decoder(&mut c).map_err(corrupted)?)),
+            1 => Ok(Node::Root(Root::decoder(&mut c).map_err(corrupted)?)),
+            _ => Err(corrupted(())),
+        }
+    }
+
+    fn max_end(&self) -> Option<K> {
+        match self {
+            Node::Leaf(leaf) => leaf.max_end(),
+            Node::Root(root) => root.max_end(),
+        }
+    }
+}
+
+/// Every file starts with the magic and the format [`VERSION`], See [`Metadata`].
+pub const MAGIC: [u8; 4] = *b"FRIX";
+pub const VERSION: u8 = 1;
+
+#[derive(Decoder, Encoder)]
+pub struct Metadata {
+    pub magic: [u8; 4],
+    pub version: u8,
+    pub page_size: u32,
+    /// `1` while the index is opened, It is cleared on drop.
+    pub is_opened: u8,
+    pub root_id: u16,
+    /// Head of the free page list, `0` if it is empty.
+    pub free: u16,
+}
+
+impl Metadata {
+    fn new<const SIZE: usize>(root_id: u16, free: u16, is_opened: u8) -> Self {
+        Self {
+            magic: MAGIC,
+            version: VERSION,
+            page_size: SIZE as u32,
+            is_opened,
+            root_id,
+            free,
+        }
+    }
+
+    /// Returns [`Error::FormatMismatch`] if the file isn't written with the current [`VERSION`] and page size,
+    /// And [`Error::AlreadyOpen`] if it is opened (or wasn't closed properly).
+    fn decode<const SIZE: usize>(page: &[u8]) -> Result<Self> {
+        let metadata: io::Result<Self> = Decoder::decoder(&mut Cursor::new(page));
+        let metadata = metadata.map_err(|_| Error::Corrupted { page: 0 })?;
+        let expected = || format!("format version {}", VERSION);
+        if metadata.magic != MAGIC {
+            return Err(Error::FormatMismatch {
+                expected: expected(),
+                found: "a file without format version".into(),
+            });
+        }
+        if metadata.version != VERSION {
+            return Err(Error::FormatMismatch {
+                expected: expected(),
+                found: format!("format version {}", metadata.version),
+            });
+        }
+        if metadata.page_size != SIZE as u32 {
+            return Err(Error::FormatMismatch {
+                expected: format!("page size {}", SIZE),
+                found: format!("page size {}", metadata.page_size),
+            });
+        }
+        if metadata.is_opened == 1 {
+            return Err(Error::AlreadyOpen);
+        }
+        Ok(metadata)
+    }
+}
+
+/// An index of intervals `[start, end)`, Every interval has a value.
+///
+/// Intervals are sorted by `(start, end)`, And every child of a [`Root`] node records the max end
+/// of its intervals, So overlap queries skip the childs that end too early.
+///
+/// The root node is kept in memory, It is written with the [`Metadata`] on drop.
+pub struct RangeIdx<K: Key, V: Value, const SIZE: usize> {
     pages: Pages<SIZE>,
+    root_id: u16,
     root: Node<K, V, SIZE>,
+    /// Head of the free page list.
+    free: u16,
+    /// Appended entries, That aren't written yet.
+    buffer: Option<Buffer<K, V, SIZE>>,
 }
 
-impl<K, V, const SIZE: usize> RangeIdx<K, V, SIZE> {
-    pub fn open(path: impl AsRef<Path>) -> io::Result<Self> {
+impl<K: Key, V: Value, const SIZE: usize> RangeIdx<K, V, SIZE> {
+    /// #### _Blocking_
+    ///
+    /// A file can be opened by a single index at a time, Otherwise [`Error::AlreadyOpen`] is returned.
+    pub fn open(path: impl AsRef<Path>) -> Result<Self> {
         let file = File::options()
             .read(true)
             .write(true)
@@ -30,14 +146,333 @@ impl<K, V, const SIZE: usize> RangeIdx<K, V, SIZE> {
             .open(path)?;
 
         let pages = Pages::open(file)?;
-        let buf = get_buf(&pages, 0u16)?;
 
+        let (root_id, root, free) = if pages.len() == 0 {
+            pages.alloc(2)?; // 1 for metadata, 1 for root node
+            (1, Node::Leaf(Leaf::new()), 0)
+        } else {
+            let metadata = Metadata::decode::<SIZE>(get_buf(&pages, 0u16)?.as_ref())?;
+            let root_id = metadata.root_id;
+            let root = Node::decoder(root_id, get_buf(&pages, root_id)?.as_ref())?;
+            (root_id, root, metadata.free)
+        };
+        // `metadata.is_opened` flag.
+        pages.write(0, encode(Metadata::new::<SIZE>(root_id, free, 1)))?;
         Ok(Self {
             pages,
-            root: todo!(),
+            root_id,
+            root,
+            free,
+            buffer: None,
         })
     }
+
+    /// #### _Blocking_
+    ///
+    /// Returns the old value, If the same interval was already present.
+    ///
+    /// Returns [`Error::InvalidKey`], If the interval is empty (`start >= end`) or an endpoint is `NaN`.
+    pub fn insert(&mut self, range: Range<K>, value: V) -> Result<Option<V>> {
+        let key = ensure_range(range)?;
+        // The last leaf may be changed, So the appended entries are written first.
+        self.flush()?;
+        self.buffer = None;
+        self.insert_entry(key, value)
+    }
+
+    fn insert_entry(&mut self, key: (K, K), value: V) -> Result<Option<V>> {
+        let mut root = mem::replace(&mut self.root, Node::Leaf(Leaf::new()));
+        let (ret, split) = match self._insert(&mut root, key, value) {
+            Ok(ok) => ok,
+            Err(err) => {
+                self.root = root;
+                return Err(err);
+            }
+        };
+        self.grow(root, split)?;
+        Ok(ret)
+    }
+
+    /// Puts the `root` back, If the root is splitted: A new root is created.
+    fn grow(&mut self, root: Node<K, V, SIZE>, split: Split<K>) -> Result<()> {
+        match split {
+            // The root is splitted, So the height of the index grows.
+            Some((mid, right, right_max)) => {
+                let (id, left_max) = (self.root_id, root.max_end().unwrap());
+                self.write(id, root)?;
+                self.root_id = self.alloc()?;
+                self.root = Node::Root(Root::create(mid, (id, left_max), (right, right_max)));
+            }
+            None => self.root = root,
+        }
+        Ok(())
+    }
+
+    /// Insert the entry into `node`, The caller is responsible for writing the `node`.
+    ///
+    /// Returns the old value, And the split of the node.
+    fn _insert(
+        &mut self,
+        node: &mut Node<K, V, SIZE>,
+        key: (K, K),
+        value: V,
+    ) -> Result<(Option<V>, Split<K>)> {
+        match node {
+            Node::Leaf(leaf) => {
+                let ret = leaf.insert(key, value);
+                if !leaf.is_full() {
+                    return Ok((ret, None));
+                }
+                let right = leaf.split_at_mid();
+                let mid = right.entries[0].0.clone();
+                let right_max = right.max_end().unwrap();
+                let right_id = self.create(Node::Leaf(right))?;
+                Ok((ret, Some((mid, right_id, right_max))))
+            }
+            Node::Root(root) => {
+                let index = root.lookup(&key);
+                let child_id = root.childs[index];
+                let mut child = self.read(child_id)?;
+                let (ret, split) = self._insert(&mut child, key, value)?;
+                // The child isn't empty after an insertion.
+                root.max_ends[index] = child.max_end().unwrap();
+                self.write(child_id, child)?;
+                if let Some((mid, right, right_max)) = split {
+                    root.insert(index, mid, right, right_max);
+                    if root.is_full() {
+                        let (other, mid) = root.split_at_mid();
+                        let other_max = other.max_end().unwrap();
+                        let other_id = self.create(Node::Root(other))?;
+                        return Ok((ret, Some((mid, other_id, other_max))));
+                    }
+                }
+                Ok((ret, None))
+            }
+        }
+    }
+
+    /// #### _Blocking_
+    ///
+    /// Removes the interval, Only if `start` and `end` are both equal.
+    ///
+    /// A leaf that is left empty is removed from the index, And its page is reused.
+    /// An appended point is removed with [`RangeIdx::remove_point`].
+    pub fn remove(&mut self, range: Range<K>) -> Result<Option<V>> {
+        let key = ensure_range(range)?;
+        self.remove_entry(key)
+    }
+
+    fn remove_entry(&mut self, key: (K, K)) -> Result<Option<V>> {
+        self.flush()?;
+        self.buffer = None;
+        let mut root = mem::replace(&mut self.root, Node::Leaf(Leaf::new()));
+        let ret = match self._remove(&mut root, &key) {
+            Ok((ret, true)) => {
+                // Every child of the root is removed.
+                root = Node::Leaf(Leaf::new());
+                Ok(ret)
+            }
+            ret => ret.map(|(ret, _)| ret),
+        };
+        self.root = root;
+        self.shrink()?;
+        ret
+    }
+
+    /// Returns the removed value, And whether the `node` is left empty.
+    fn _remove(&mut self, node: &mut Node<K, V, SIZE>, key: &(K, K)) -> Result<(Option<V>, bool)> {
+        match node {
+            Node::Leaf(leaf) => {
+                let ret = leaf.remove(key);
+                Ok((ret, leaf.entries.is_empty()))
+            }
+            Node::Root(root) => {
+                let index = root.lookup(key);
+                let child_id = root.childs[index];
+                let mut child = self.read(child_id)?;
+                let (ret, empty) = self._remove(&mut child, key)?;
+                if empty {
+                    root.remove(index);
+                    self.free(child_id)?;
+                } else if ret.is_some() {
+                    root.max_ends[index] = child.max_end().unwrap();
+                    self.write(child_id, child)?;
+                }
+                Ok((ret, root.childs.is_empty()))
+            }
+        }
+    }
+
+    /// While the root has a single child, The child becomes the root. So the height of the index shrinks.
+    fn shrink(&mut self) -> Result<()> {
+        while let Node::Root(root) = &self.root {
+            if root.childs.len() != 1 {
+                break;
+            }
+            let child_id = root.childs[0];
+            self.root = self.read(child_id)?;
+            self.free(self.root_id)?;
+            self.root_id = child_id;
+        }
+        Ok(())
+    }
+
+    /// #### _Blocking_
+    ///
+    /// Intervals that overlap with `range` (`start < range.end` and `end > range.start`), Sorted by `(start, end)`.
+    /// An appended point overlaps, If it is in the `range`.
+    pub fn overlapping(&self, range: Range<K>) -> Result<Vec<(Range<K>, V)>> {
+        let before = |start: &K| *start < range.end;
+        self.search(&before, &range.start)
+    }
+
+    /// #### _Blocking_
+    ///
+    /// Intervals that contain the `point` (`start <= point < end`), Sorted by `(start, end)`.
+    /// An appended point contains only itself.
+    pub fn containing(&self, point: K) -> Result<Vec<(Range<K>, V)>> {
+        let before = |start: &K| *start <= point;
+        self.search(&before, &point)
+    }
+
+    /// Searches the index and the appended entries, That aren't written yet.
+    fn search(&self, before: &impl Fn(&K) -> bool, after: &K) -> Result<Vec<(Range<K>, V)>> {
+        let mut out = vec![];
+        self._search(&self.root, before, after, &mut out)?;
+        if let Some(buffer) = &self.buffer {
+            for ((start, end), value) in &buffer.entries {
+                if hit(start, end, before, after) {
+                    out.push((start.clone()..end.clone(), value.clone()));
+                }
+            }
+        }
+        Ok(out)
+    }
+
+    /// Collects the intervals, Whose `start` is accepted by `before` and whose `end` is greater than `after`.
+    fn _search(
+        &self,
+        node: &Node<K, V, SIZE>,
+        before: &impl Fn(&K) -> bool,
+        after: &K,
+        out: &mut Vec<(Range<K>, V)>,
+    ) -> Result<()> {
+        match node {
+            Node::Leaf(leaf) => {
+                for ((start, end), value) in leaf.entries.iter() {
+                    if !before(start) {
+                        break;
+                    }
+                    if hit(start, end, before, after) {
+                        out.push((start.clone()..end.clone(), value.clone()));
+                    }
+                }
+            }
+            Node::Root(root) => {
+                for (index, &child) in root.childs.iter().enumerate() {
+                    // Every interval of the child starts at or after its separator key.
+                    if index > 0 && !before(&root.keys[index - 1].0) {
+                        break;
+                    }
+                    // `>=`, An appended point may end at `after`.
+                    if root.max_ends[index] >= *after {
+                        self._search(&self.read(child)?, before, after, out)?;
+                    }
+                }
+            }
+        }
+        Ok(())
+    }
+
+    fn read(&self, id: u16) -> Result<Node<K, V, SIZE>> {
+        Node::decoder(id, get_buf(&self.pages, id)?.as_ref())
+    }
+
+    fn write(&self, id: u16, node: Node<K, V, SIZE>) -> Result<()> {
+        Ok(self.pages.write(id.into(), node.encode())?)
+    }
+
+    fn create(&mut self, node: Node<K, V, SIZE>) -> Result<u16> {
+        let id = self.alloc()?;
+        self.write(id, node)?;
+        Ok(id)
+    }
+
+    /// Reuses a page from the free list, Or allocates one.
+    ///
+    /// Returns [`Error::InvalidInput`] if the page number doesn't fit in `u16`.
+    fn alloc(&mut self) -> Result<u16> {
+        match self.free {
+            0 => {
+                let id = self.pages.alloc(1)?;
+                u16::try_from(id).map_err(|_| {
+                    Error::InvalidInput(format!("The page number {} doesn't fit in `u16`.", id))
+                })
+            }
+            id => {
+                let buf = get_buf(&self.pages, id)?;
+                let free: io::Result<(u8, u16)> = Decoder::decoder(&mut Cursor::new(buf.as_ref()));
+                self.free = free.map_err(|_| Error::Corrupted { page: id.into() })?.1;
+                Ok(id)
+            }
+        }
+    }
+
+    /// Pushes the page to the free list.
+    ///
+    /// Free page layout: Node type (2) + next free page (2)
+    fn free(&mut self, id: u16) -> Result<()> {
+        let mut arr = ArrayBuf::new();
+        (2u8, self.free).encoder(&mut arr);
+        self.pages.write(id.into(), to_page(arr))?;
+        self.free = id;
+        Ok(())
+    }
+}
+
+impl<K: Key, V: Value, const SIZE: usize> Drop for RangeIdx<K, V, SIZE> {
+    fn drop(&mut self) {
+        self.flush().unwrap();
+        let root = mem::replace(&mut self.root, Node::Leaf(Leaf::new()));
+        self.write(self.root_id, root).unwrap();
+        let metadata = Metadata::new::<SIZE>(self.root_id, self.free, 0);
+        self.pages.write(0, encode(metadata)).unwrap();
+    }
+}
+
+/// Returns the interval as `(start, end)`, If it isn't empty.
+fn ensure_range<K: Key>(range: Range<K>) -> Result<(K, K)> {
+    match range.start.partial_cmp(&range.end) {
+        Some(Ordering::Less) => Ok((range.start, range.end)),
+        _ => Err(Error::InvalidKey),
+    }
+}
+
+/// The interval `[start, end)` is accepted, Or the point `[start, start]` is in the query.
+fn hit<K: Key>(start: &K, end: &K, before: &impl Fn(&K) -> bool, after: &K) -> bool {
+    before(start) && (end > after || start == end && end == after)
+}
+
+fn max<K: PartialOrd>(a: K, b: K) -> K {
+    if b > a {
+        b
+    } else {
+        a
+    }
 }
 
+fn get_buf<const SIZE: usize>(pages: &Pages<SIZE>, id: u16) -> Result<[u8; SIZE]> {
+    Ok(pages.read(id.into())?)
+}
+
+fn encode<const SIZE: usize>(value: impl Encoder) -> [u8; SIZE] {
+    let mut arr = ArrayBuf::new();
+    value.encoder(&mut arr);
+    to_page(arr)
+}
 
-// ----------------------------------------------------------------------
\ No newline at end of file
+fn to_page<const SIZE: usize>(arr: ArrayBuf<u8, SIZE>) -> [u8; SIZE] {
+    let mut buf = [0; SIZE];
+    buf[..arr.len()].copy_from_slice(&arr);
+    buf
+}
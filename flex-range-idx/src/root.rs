@@ -2,8 +2,76 @@ use crate::entry::Key;
 use bin_layout::{ Array, Encoder, Decoder, Cursor};
 
 pub struct Root<K, const SIZE: usize> {
-    keys: Vec<K>,
-    childs: Vec<u16>,
+    pub keys: Vec<(K, K)>,
+    pub childs: Vec<u16>,
+    /// Upper bound of the `end` of the intervals, For every child.
+    pub max_ends: Vec<K>,
+}
+
+impl<K: Key, const SIZE: usize> Root<K, SIZE> {
+    pub const fn capacity() -> usize {
+        // BlockSize - (Node type (1) + keys len (2)), Every child takes a key, A page number (2) and a max end.
+        (SIZE - 3) / (3 * K::SIZE + 2)
+    }
+
+    /// `left` and `right` are the page numbers of the childs, With their max end.
+    pub fn create(key: (K, K), left: (u16, K), right: (u16, K)) -> Self {
+        Self {
+            keys: vec![key],
+            childs: vec![left.0, right.0],
+            max_ends: vec![left.1, right.1],
+        }
+    }
+
+    pub fn is_full(&self) -> bool {
+        self.childs.len() >= Self::capacity()
+    }
+
+    /// Index of the child, That may contain the `key`.
+    pub fn lookup(&self, key: &(K, K)) -> usize {
+        match self
+            .keys
+            .binary_search_by(|k| k.partial_cmp(key).expect("Key can't be `NaN`"))
+        {
+            Ok(i) => i + 1,
+            Err(i) => i,
+        }
+    }
+
+    /// Insert the `child` after the child at `index`, With its separator `key`.
+    pub fn insert(&mut self, index: usize, key: (K, K), child: u16, max_end: K) {
+        self.keys.insert(index, key);
+        self.childs.insert(index + 1, child);
+        self.max_ends.insert(index + 1, max_end);
+    }
+
+    /// Remove the child at `index`, With its separator key.
+    pub fn remove(&mut self, index: usize) {
+        self.childs.remove(index);
+        self.max_ends.remove(index);
+        if !self.keys.is_empty() {
+            self.keys.remove(index.saturating_sub(1));
+        }
+    }
+
+    pub fn max_end(&self) -> Option<K> {
+        self.max_ends.iter().cloned().reduce(crate::max)
+    }
+
+    /// This function splits `Self` at the middle, and returns the other half. with reminder key.
+    pub fn split_at_mid(&mut self) -> (Self, (K, K)) {
+        self.split_at(self.childs.len() / 2)
+    }
+
+    /// The other half starts with the child at `mid`.
+    pub fn split_at(&mut self, mid: usize) -> (Self, (K, K)) {
+        let other = Self {
+            keys: self.keys.drain(mid..).collect(),
+            childs: self.childs.drain(mid..).collect(),
+            max_ends: self.max_ends.drain(mid..).collect(),
+        };
+        (other, self.keys.pop().unwrap())
+    }
 }
 
 impl<K: Key, const SIZE: usize> Encoder for Root<K, SIZE> {
@@ -15,6 +83,9 @@ impl<K: Key, const SIZE: usize> Encoder for Root<K, SIZE> {
         for child in self.childs {
             child.encoder(buf);
         }
+        for max_end in self.max_ends {
+            max_end.encoder(buf);
+        }
     }
 }
 
@@ -24,13 +95,17 @@ impl<'de, K: Key, const SIZE: usize> Decoder<'de, ()> for Root<K, SIZE> {
         let mut this = Self{
             keys: Vec::with_capacity(keys_len as usize),
             childs: Vec::with_capacity(keys_len as usize + 1),
+            max_ends: Vec::with_capacity(keys_len as usize + 1),
         };
         for _ in 0..keys_len {
-            this.keys.push(K::decoder(c)?);
+            this.keys.push(<(K, K)>::decoder(c)?);
         }
         for _ in 0..keys_len + 1 {
             this.childs.push(u16::decoder(c)?);
         }
+        for _ in 0..keys_len + 1 {
+            this.max_ends.push(K::decoder(c)?);
+        }
         Ok(this)
     }
 }
@@ -0,0 +1,130 @@
+use flex_range_idx::{Error, RangeIdx};
+use std::{fs, fs::remove_file, io::Result};
+
+type Idx = RangeIdx<u32, u16, 128>;
+
+// Node type (1) + entries len (2), Every entry takes a start (4), An end (4) and a value (2).
+const LEAF_CAPACITY: u64 = (128 - 3) / 10;
+
+fn points(keys: impl Iterator<Item = u32>) -> Vec<(u32, u16)> {
+    keys.map(|key| (key * 3, key as u16)).collect()
+}
+
+#[test]
+fn append_time_series() -> Result<()> {
+    let _ = remove_file("append_time_series");
+    {
+        let mut idx = Idx::open("append_time_series")?;
+        for (key, value) in points(0..5000) {
+            idx.append(key, value)?;
+        }
+        assert!(matches!(idx.append(3, 0), Err(Error::InvalidKey)));
+        assert!(matches!(idx.append(4997 * 3, 0), Err(Error::InvalidKey)));
+
+        // Buffered entries are visible.
+        assert_eq!(idx.scan(0..30)?, points(0..10));
+        assert_eq!(idx.scan(14_950..15_100)?, points(4984..5000));
+        assert_eq!(idx.containing(4999 * 3)?, vec![(14_997..14_997, 4999)]);
+        assert_eq!(idx.containing(4999 * 3 + 1)?, vec![]);
+        assert_eq!(idx.overlapping(30..36)?, vec![(30..30, 10), (33..33, 11)]);
+    }
+    // Every leaf (except the last) is filled.
+    let pages = fs::metadata("append_time_series")?.len() / 128;
+    assert!(pages < 2 + 5000 / LEAF_CAPACITY * 6 / 5);
+    {
+        let mut idx = Idx::open("append_time_series")?;
+        assert_eq!(idx.scan(0..15_000)?, points(0..5000));
+        assert!(matches!(idx.append(14_997, 0), Err(Error::InvalidKey)));
+        for (key, value) in points(5000..6000) {
+            idx.append(key, value)?;
+        }
+        // Inserting an interval writes the buffer first, Appending continues after it.
+        idx.insert(17_000..17_500, 7)?;
+        assert!(matches!(idx.append(17_000, 0), Err(Error::InvalidKey)));
+        for (key, value) in points(6000..7000) {
+            idx.append(key, value)?;
+        }
+        assert_eq!(idx.scan(0..21_000)?.len(), 7001);
+        assert_eq!(idx.containing(17_101)?, vec![(17_000..17_500, 7)]);
+    }
+    remove_file("append_time_series")
+}
+
+#[test]
+fn drop_leading_leaves() -> Result<()> {
+    let _ = remove_file("drop_leading_leaves");
+    {
+        let mut idx = Idx::open("drop_leading_leaves")?;
+        for (key, value) in points(0..3000) {
+            idx.append(key, value)?;
+        }
+        idx.drop_before(6000)?;
+        // Only whole leaves are dropped.
+        let kept = idx.scan(0..6000)?;
+        assert!(kept.len() <= LEAF_CAPACITY as usize);
+        assert_eq!(kept, points(2000 - kept.len() as u32..2000));
+        assert_eq!(idx.scan(6000..9000)?, points(2000..3000));
+
+        idx.drop_before(9000)?;
+        for (key, value) in points(3000..3100) {
+            idx.append(key, value)?;
+        }
+    }
+    let idx = Idx::open("drop_leading_leaves")?;
+    let kept = idx.scan(0..9000)?;
+    assert!(kept.len() <= LEAF_CAPACITY as usize);
+    assert_eq!(idx.scan(9000..10_000)?, points(3000..3100));
+    drop(idx);
+    remove_file("drop_leading_leaves")
+}
+
+#[test]
+fn drop_frees_leaves() -> Result<()> {
+    let _ = remove_file("drop_frees_leaves");
+    let len = {
+        let mut idx = Idx::open("drop_frees_leaves")?;
+        for (key, value) in points(0..3000) {
+            idx.append(key, value)?;
+        }
+        idx.drop_before(6000)?;
+        fs::metadata("drop_frees_leaves")?.len()
+    };
+    {
+        // Pages of the dropped leaves are reused.
+        let mut idx = Idx::open("drop_frees_leaves")?;
+        for (key, value) in points(3000..4000) {
+            idx.append(key, value)?;
+        }
+    }
+    assert_eq!(fs::metadata("drop_frees_leaves")?.len(), len);
+    let idx = Idx::open("drop_frees_leaves")?;
+    assert_eq!(idx.scan(6000..12_000)?, points(2000..4000));
+    drop(idx);
+    remove_file("drop_frees_leaves")
+}
+
+#[test]
+fn remove_appended_points() -> Result<()> {
+    let _ = remove_file("remove_appended_points");
+    {
+        let mut idx = Idx::open("remove_appended_points")?;
+        for (key, value) in points(0..1000) {
+            idx.append(key, value)?;
+        }
+        // An appended point isn't an interval, So `remove` rejects it.
+        assert!(matches!(idx.remove(30..30), Err(Error::InvalidKey)));
+        assert_eq!(idx.remove_point(30)?, Some(10));
+        assert_eq!(idx.remove_point(30)?, None);
+        assert_eq!(idx.remove_point(31)?, None);
+        // The last point is buffered.
+        assert_eq!(idx.remove_point(999 * 3)?, Some(999));
+        assert_eq!(idx.scan(27..36)?, vec![(27, 9), (33, 11)]);
+        idx.append(999 * 3, 999)?;
+    }
+    let idx = Idx::open("remove_appended_points")?;
+    let mut expected = points(0..1000);
+    expected.remove(10);
+    assert_eq!(idx.scan(0..3000)?, expected);
+    drop(idx);
+    remove_file("remove_appended_points")
+}
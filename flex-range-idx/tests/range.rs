@@ -0,0 +1,127 @@
+use flex_range_idx::{Error, RangeIdx};
+use std::{fs::remove_file, io::Result, ops::Range};
+
+type Idx = RangeIdx<u32, u16, 128>;
+
+/// Intervals of a pseudo random length, Which may overlap with each other.
+fn intervals() -> Vec<(Range<u32>, u16)> {
+    let mut seed = 7u32;
+    (0..1500u16)
+        .map(|i| {
+            seed = seed.wrapping_mul(1103515245).wrapping_add(12345);
+            let start = (seed >> 8) % 10_000;
+            let len = 1 + (seed >> 4) % if i % 10 == 0 { 2000 } else { 50 };
+            (start..start + len, i)
+        })
+        .collect()
+}
+
+fn expected(
+    intervals: &[(Range<u32>, u16)],
+    filter: impl Fn(&Range<u32>) -> bool,
+) -> Vec<(Range<u32>, u16)> {
+    let mut out: Vec<_> = intervals
+        .iter()
+        .filter(|(range, _)| filter(range))
+        .cloned()
+        .collect();
+    out.sort_by_key(|(range, _)| (range.start, range.end));
+    out
+}
+
+#[test]
+fn overlap_queries() -> Result<()> {
+    let _ = remove_file("overlap_queries");
+    let mut intervals = intervals();
+    intervals.sort_by_key(|(range, _)| (range.start, range.end));
+    intervals.dedup_by_key(|(range, _)| (range.start, range.end));
+    {
+        let mut idx = Idx::open("overlap_queries")?;
+        for (range, value) in &intervals {
+            assert_eq!(idx.insert(range.clone(), *value)?, None);
+        }
+        assert!(matches!(idx.insert(5..5, 0), Err(Error::InvalidKey)));
+
+        let (range, value) = intervals.remove(100);
+        assert_eq!(idx.remove(range.clone())?, Some(value));
+        assert_eq!(idx.remove(range)?, None);
+        let (range, value) = &mut intervals[200];
+        assert_eq!(idx.insert(range.clone(), 42)?, Some(*value));
+        *value = 42;
+    }
+    let idx = Idx::open("overlap_queries")?;
+    for query in [0..1, 300..310, 5000..5001, 9990..12000, 12000..13000] {
+        let overlap = |r: &Range<u32>| r.start < query.end && r.end > query.start;
+        assert_eq!(
+            idx.overlapping(query.clone())?,
+            expected(&intervals, overlap)
+        );
+    }
+    for point in [0, 1, 777, 5000, 9999, 11_000] {
+        let contains = |r: &Range<u32>| r.contains(&point);
+        assert_eq!(idx.containing(point)?, expected(&intervals, contains));
+    }
+    drop(idx);
+    remove_file("overlap_queries")
+}
+
+#[test]
+fn remove_frees_leaves() -> Result<()> {
+    let _ = remove_file("remove_frees_leaves");
+    let len = {
+        let mut idx = Idx::open("remove_frees_leaves")?;
+        for i in 0..2000 {
+            idx.insert(i..i + 10, i as u16)?;
+        }
+        let len = std::fs::metadata("remove_frees_leaves")?.len();
+        for i in 0..2000 {
+            assert_eq!(idx.remove(i..i + 10)?, Some(i as u16));
+        }
+        assert_eq!(idx.containing(5)?, vec![]);
+        len
+    };
+    {
+        // Pages of the removed leaves are reused.
+        let mut idx = Idx::open("remove_frees_leaves")?;
+        for i in 0..2000 {
+            idx.insert(i..i + 10, i as u16)?;
+        }
+        for i in (0..2000).step_by(2) {
+            assert_eq!(idx.remove(i..i + 10)?, Some(i as u16));
+        }
+    }
+    assert_eq!(std::fs::metadata("remove_frees_leaves")?.len(), len);
+    let idx = Idx::open("remove_frees_leaves")?;
+    let expected: Vec<_> = (1000..1010)
+        .filter(|i| i % 2 == 1)
+        .map(|i| (i..i + 10, i as u16))
+        .collect();
+    assert_eq!(idx.overlapping(1009..1010)?, expected);
+    drop(idx);
+    remove_file("remove_frees_leaves")
+}
+
+#[test]
+fn open_guard() -> flex_range_idx::Result<()> {
+    let _ = remove_file("open_guard");
+    {
+        let _idx = Idx::open("open_guard")?;
+        assert!(matches!(Idx::open("open_guard"), Err(Error::AlreadyOpen)));
+    }
+    match RangeIdx::<u32, u16, 256>::open("open_guard") {
+        Err(Error::FormatMismatch { expected, found }) => {
+            assert_eq!(expected, "page size 256");
+            assert_eq!(found, "page size 128");
+        }
+        _ => panic!("a different page size is accepted"),
+    }
+    // A file without the magic, Like one that was written by an older version.
+    std::fs::write("open_guard", [1, 0, 0, 0].repeat(64))?;
+    match Idx::open("open_guard") {
+        Err(Error::FormatMismatch { found, .. }) => {
+            assert_eq!(found, "a file without format version")
+        }
+        _ => panic!("a file without format version is accepted"),
+    }
+    Ok(remove_file("open_guard")?)
+}
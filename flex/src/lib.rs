@@ -0,0 +1,67 @@
+use std::{fmt, io};
+
+pub type Result<T, E = Error> = std::result::Result<T, E>;
+
+/// Error type, Shared by every index crate.
+#[derive(Debug)]
+pub enum Error {
+    /// I/O error of the underlying file.
+    Io(io::Error),
+    /// The page couldn't be decoded, Like an unknown node type or a length that exceeds the capacity.
+    Corrupted { page: u64 },
+    /// The file was created with a different format (key, value or page size).
+    FormatMismatch { expected: String, found: String },
+    /// The file is already opened, Or wasn't closed properly.
+    AlreadyOpen,
+    /// A key (or an entry) doesn't fit in a page.
+    KeyTooLarge,
+    /// The key can't be used here, Like `NaN` or a key that is out of order.
+    InvalidKey,
+    /// An argument (or the state of the index) isn't valid for the operation.
+    InvalidInput(String),
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Error::Io(err) => err.fmt(f),
+            Error::Corrupted { page } => write!(f, "The page {} is corrupted.", page),
+            Error::FormatMismatch { expected, found } => {
+                write!(f, "Expected: {}, but got: {}", expected, found)
+            }
+            Error::AlreadyOpen => f.write_str("The file is already opened."),
+            Error::KeyTooLarge => f.write_str("The key doesn't fit in a page."),
+            Error::InvalidKey => f.write_str("Invalid key."),
+            Error::InvalidInput(msg) => f.write_str(msg),
+        }
+    }
+}
+
+impl std::error::Error for Error {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Error::Io(err) => Some(err),
+            _ => None,
+        }
+    }
+}
+
+impl From<io::Error> for Error {
+    fn from(err: io::Error) -> Self {
+        Error::Io(err)
+    }
+}
+
+impl From<Error> for io::Error {
+    fn from(err: Error) -> Self {
+        let kind = match err {
+            Error::Io(err) => return err,
+            Error::Corrupted { .. } | Error::FormatMismatch { .. } => io::ErrorKind::InvalidData,
+            Error::AlreadyOpen => io::ErrorKind::AddrInUse,
+            Error::KeyTooLarge | Error::InvalidKey | Error::InvalidInput(_) => {
+                io::ErrorKind::InvalidInput
+            }
+        };
+        io::Error::new(kind, err)
+    }
+}